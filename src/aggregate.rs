@@ -0,0 +1,65 @@
+use std::future::poll_fn;
+use std::pin::Pin;
+
+use futures_core::Stream;
+
+use crate::{merge_readers, AccessMode, Device, DeviceCriteria, DeviceInfo, HidResult, MergedReports};
+
+/// Reads the input reports of multiple devices as if they were a single device
+///
+/// This is a convenience wrapper around [merge_readers] that reads into a caller provided buffer.
+/// Failed reads are logged and skipped, disconnected devices are removed and the remaining devices continue to be read.
+pub struct AggregateReader {
+    reports: MergedReports,
+    current: Option<DeviceInfo>
+}
+
+impl AggregateReader {
+    /// Creates a new reader for the given devices
+    pub fn new(devices: impl IntoIterator<Item = Device>) -> Self {
+        Self {
+            reports: merge_readers(devices.into_iter().collect()),
+            current: None
+        }
+    }
+
+    /// Opens all devices that match the given [DeviceCriteria] in [AccessMode::Read]
+    ///
+    /// Devices that fail to open are skipped.
+    pub async fn open(criteria: &DeviceCriteria) -> HidResult<Self> {
+        let mut devices = DeviceInfo::enumerate().await?;
+        let mut opened = Vec::new();
+        while let Some(info) = poll_fn(|cx| Pin::new(&mut devices).poll_next(cx)).await {
+            if !criteria.matches(&info) {
+                continue;
+            }
+            match info.open(AccessMode::Read).await {
+                Ok(device) => opened.push(device),
+                Err(e) => log::trace!("Failed to open {:?} for the aggregate reader\n\tbecause {e:?}", info.name)
+            }
+        }
+        Ok(Self::new(opened))
+    }
+
+    /// Reads the next input report of any device and returns the [DeviceInfo] of the device it came from
+    ///
+    /// Reports that don't fit into `buf` are truncated. Returns `None` once all devices were removed.
+    pub async fn read_input_report(&mut self, buf: &mut [u8]) -> Option<(&DeviceInfo, usize)> {
+        loop {
+            let (info, result) = poll_fn(|cx| Pin::new(&mut self.reports).poll_next(cx)).await?;
+            match result {
+                Ok(report) => {
+                    let size = report.len().min(buf.len());
+                    buf[..size].copy_from_slice(&report[..size]);
+                    return Some((self.current.insert(info), size));
+                }
+                Err(e) => log::trace!("Skipping input report of {:?} in the aggregate reader\n\tbecause {e:?}", info.name)
+            }
+        }
+    }
+
+    /// The [DeviceInfo]s of all devices that are still being read
+    pub fn sources(&self) -> impl Iterator<Item = &DeviceInfo> {
+        self.reports.sources()
+    }
+}
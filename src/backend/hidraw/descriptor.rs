@@ -1,8 +1,11 @@
 use std::fs::File;
 use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::os::fd::{AsRawFd, OwnedFd};
 use std::path::Path;
 
-use crate::HidResult;
+use crate::backend::hidraw::ioctl::{hidraw_ioc_grdesc, hidraw_ioc_grdescsize, HidrawReportDescriptorRaw, HID_MAX_DESCRIPTOR_SIZE};
+use crate::backend::BackendError;
+use crate::{HidError, HidResult};
 
 #[derive(Default)]
 pub struct HidrawReportDescriptor(Vec<u8>);
@@ -18,6 +21,22 @@ impl HidrawReportDescriptor {
         Ok(HidrawReportDescriptor(buf))
     }
 
+    /// Retrieve the descriptor from an opened device node
+    pub fn from_fd(fd: &OwnedFd) -> HidResult<Self> {
+        let mut size = 0i32;
+        unsafe { hidraw_ioc_grdescsize(fd.as_raw_fd(), &mut size) }.map_err(BackendError::from)?;
+        let size = checked_descriptor_size(size)?;
+
+        let mut descriptor = HidrawReportDescriptorRaw {
+            size: size as u32,
+            value: [0; HID_MAX_DESCRIPTOR_SIZE]
+        };
+        unsafe { hidraw_ioc_grdesc(fd.as_raw_fd(), &mut descriptor) }.map_err(BackendError::from)?;
+
+        let size = (descriptor.size as usize).min(HID_MAX_DESCRIPTOR_SIZE);
+        Ok(HidrawReportDescriptor(descriptor.value[..size].to_vec()))
+    }
+
     /// Create a descriptor from a slice
     ///
     /// It returns an error if the value slice is too large for it to be a HID
@@ -27,6 +46,10 @@ impl HidrawReportDescriptor {
         Ok(HidrawReportDescriptor(value.to_vec()))
     }
 
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
     pub fn usages(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
         UsageIterator {
             usage_page: 0,
@@ -35,6 +58,16 @@ impl HidrawReportDescriptor {
     }
 }
 
+/// Validates the descriptor size reported by the kernel
+///
+/// The kernel rejects requests for HID_MAX_DESCRIPTOR_SIZE bytes or more, so a larger size can only come from a broken driver
+fn checked_descriptor_size(size: i32) -> HidResult<usize> {
+    usize::try_from(size)
+        .ok()
+        .filter(|size| *size < HID_MAX_DESCRIPTOR_SIZE)
+        .ok_or_else(|| HidError::custom(format!("Implausible report descriptor size of {size} bytes")))
+}
+
 /// Iterates over the values in a HidrawReportDescriptor
 struct UsageIterator<'a> {
     usage_page: u16,
@@ -132,8 +165,9 @@ fn next_hid_usage(cursor: &mut Cursor<&Vec<u8>>, mut usage_page: u16) -> Option<
 ///
 /// Returns data_len and key_size when successful
 fn hid_item_size(key: u8, cursor: &mut Cursor<&Vec<u8>>) -> Option<(usize, usize)> {
-    // Long Item. Next byte contains the length of the data section.
-    if (key & 0xf0) == 0xf0 {
+    // Long Item. Next byte contains the length of the data section, followed by the actual tag.
+    // Other items with the tag 0xf are reserved short items and are skipped like any other short item.
+    if key == 0xfe {
         if let Some(Ok(len)) = cursor.bytes().next() {
             return Some((len.into(), 3));
         }
@@ -159,3 +193,35 @@ fn hid_report_bytes(cursor: &mut Cursor<&Vec<u8>>, num_bytes: usize) -> HidResul
 
     Ok(u32::from_le_bytes(bytes))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn descriptor_size_guard() {
+        assert_eq!(checked_descriptor_size(0).unwrap(), 0);
+        assert_eq!(checked_descriptor_size(4095).unwrap(), 4095);
+        assert!(checked_descriptor_size(4096).is_err());
+        assert!(checked_descriptor_size(4097).is_err());
+        assert!(checked_descriptor_size(-1).is_err());
+    }
+
+    #[test]
+    fn long_item_between_collections() {
+        let descriptor = HidrawReportDescriptor::from_slice(&[
+            0x05, 0x01, // Usage Page (Generic Desktop)
+            0x09, 0x06, // Usage (Keyboard)
+            0xa1, 0x01, // Collection (Application)
+            0xc0, // End Collection
+            // Long item with a data size of 4 and tag 0xf0, the data looks like a Usage and a Collection item
+            0xfe, 0x04, 0xf0, 0x09, 0x02, 0xa1, 0x01,
+            0x05, 0x0c, // Usage Page (Consumer)
+            0x09, 0x01, // Usage (Consumer Control)
+            0xa1, 0x01, // Collection (Application)
+            0xc0 // End Collection
+        ])
+        .unwrap();
+        assert_eq!(descriptor.usages().collect::<Vec<_>>(), [(0x01, 0x06), (0x0c, 0x01)]);
+    }
+}
@@ -1,26 +1,68 @@
 //! The IOCTL calls we need for the native linux backend
 
-use nix::ioctl_read;
+use nix::{ioctl_read, ioctl_read_buf, ioctl_readwrite_buf};
 
 // From linux/hidraw.h
 const HIDRAW_IOC_MAGIC: u8 = b'H';
 const HIDRAW_IOC_GRDESCSIZE: u8 = 0x01;
-//const HIDRAW_SET_FEATURE: u8 = 0x06;
-//const HIDRAW_GET_FEATURE: u8 = 0x07;
+const HIDRAW_IOC_GRDESC: u8 = 0x02;
+const HIDRAW_IOC_GRAWINFO: u8 = 0x03;
+const HIDRAW_IOC_GRAWNAME: u8 = 0x04;
+const HIDRAW_IOC_GRAWPHYS: u8 = 0x05;
+const HIDRAW_SET_FEATURE: u8 = 0x06;
+const HIDRAW_GET_FEATURE: u8 = 0x07;
+const HIDRAW_SET_OUTPUT: u8 = 0x0b;
+const HIDRAW_GET_OUTPUT: u8 = 0x0c;
+
+// From linux/hid.h
+pub const HID_MAX_DESCRIPTOR_SIZE: usize = 4096;
+
+/// Mirrors `struct hidraw_report_descriptor` from linux/hidraw.h
+#[repr(C)]
+pub struct HidrawReportDescriptorRaw {
+    pub size: u32,
+    pub value: [u8; HID_MAX_DESCRIPTOR_SIZE]
+}
+
+/// Mirrors `struct hidraw_devinfo` from linux/hidraw.h
+#[repr(C)]
+#[derive(Debug, Default, Copy, Clone)]
+pub struct HidrawDevInfo {
+    pub bustype: u32,
+    pub vendor: i16,
+    pub product: i16
+}
 
 ioctl_read!(hidraw_ioc_grdescsize, HIDRAW_IOC_MAGIC, HIDRAW_IOC_GRDESCSIZE, i32);
+ioctl_read!(hidraw_ioc_grdesc, HIDRAW_IOC_MAGIC, HIDRAW_IOC_GRDESC, HidrawReportDescriptorRaw);
+ioctl_read!(hidraw_ioc_grawinfo, HIDRAW_IOC_MAGIC, HIDRAW_IOC_GRAWINFO, HidrawDevInfo);
+ioctl_read_buf!(hidraw_ioc_grawname, HIDRAW_IOC_MAGIC, HIDRAW_IOC_GRAWNAME, u8);
+ioctl_read_buf!(hidraw_ioc_grawphys, HIDRAW_IOC_MAGIC, HIDRAW_IOC_GRAWPHYS, u8);
 
-/*
 ioctl_readwrite_buf!(
     hidraw_ioc_set_feature,
     HIDRAW_IOC_MAGIC,
     HIDRAW_SET_FEATURE,
     u8
 );
+
 ioctl_readwrite_buf!(
     hidraw_ioc_get_feature,
     HIDRAW_IOC_MAGIC,
     HIDRAW_GET_FEATURE,
     u8
 );
-*/
+
+ioctl_readwrite_buf!(
+    hidraw_ioc_set_output,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_SET_OUTPUT,
+    u8
+);
+
+ioctl_readwrite_buf!(
+    hidraw_ioc_get_output,
+    HIDRAW_IOC_MAGIC,
+    HIDRAW_GET_OUTPUT,
+    u8
+);
@@ -1,30 +1,73 @@
 mod descriptor;
 mod ioctl;
+mod uhid;
+mod usbfs;
 mod utils;
+#[cfg(test)]
+mod tests;
 
-use std::fs::{OpenOptions, read_dir, read_to_string};
+use std::fs::{canonicalize, OpenOptions, read_dir, read_link, read_to_string};
+use std::io::ErrorKind;
 use std::os::fd::{AsRawFd, OwnedFd};
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use futures_core::Stream;
+use nix::errno::Errno;
 use nix::fcntl::OFlag;
+use nix::libc::c_int;
+use nix::poll::{poll, PollFd, PollFlags};
+use nix::sys::epoll::{Epoll, EpollCreateFlags, EpollEvent, EpollFlags};
 use nix::unistd::{read, write};
 
 use crate::backend::hidraw::descriptor::HidrawReportDescriptor;
+use crate::backend::hidraw::usbfs::UsbInterface;
 use crate::backend::hidraw::utils::{iter, TryIterExt};
-use crate::{ensure, DeviceInfo, ErrorSource, HidError, HidResult, SerialNumberExt, AccessMode};
+use crate::{ensure, BusType, DeviceInfo, RetryPolicy, ErrorSource, HidError, HidResult, HidrawDeviceInfoExt, ManufacturerExt, ReportIds, SerialNumberExt, AccessMode};
 
 use crate::backend::hidraw::async_api::{AsyncFd, read_with, write_with};
-use crate::backend::hidraw::ioctl::hidraw_ioc_grdescsize;
+
+pub use crate::backend::hidraw::uhid::{VirtualDevice, VirtualDeviceInfo};
+use crate::backend::hidraw::ioctl::{hidraw_ioc_grawinfo, hidraw_ioc_grawname, hidraw_ioc_grawphys, hidraw_ioc_grdescsize, hidraw_ioc_get_feature, hidraw_ioc_get_output, hidraw_ioc_set_feature, hidraw_ioc_set_output, HidrawDevInfo};
 
 pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Send + Unpin> {
+    type QueryFn = fn(PathBuf) -> HidResult<Vec<DeviceInfo>>;
+    let (devices, query): (Vec<PathBuf>, QueryFn) = match read_dir("/sys/class/hidraw/") {
+        Ok(entries) => (entries.map(|r| r.map(|e| e.path())).try_collect_vec()?, get_device_info_raw),
+        Err(e) => {
+            log::trace!("Failed to read /sys/class/hidraw/, falling back to scanning /dev/\n\tbecause {e:?}");
+            let nodes = read_dir("/dev/")?
+                .map(|r| r.map(|e| e.path()))
+                .try_collect_vec()?
+                .into_iter()
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with("hidraw"))
+                })
+                .collect();
+            (nodes, get_device_info_from_node)
+        }
+    };
+    let devices = devices
+        .into_iter()
+        .map(query)
+        .filter_map(|r| {
+            r.map_err(|e| log::trace!("Failed to query device information\n\tbecause {e:?}"))
+                .ok()
+        })
+        .flatten();
+    Ok(iter(devices))
+}
+
+pub async fn enumerate_passive() -> HidResult<impl Stream<Item = DeviceInfo> + Send + Unpin> {
+    // Opening a hidraw node resumes a suspended usb device, so only the attributes cached in sysfs are used
     let devices = read_dir("/sys/class/hidraw/")?
         .map(|r| r.map(|e| e.path()))
-        .try_collect_vec()?;
-    let devices = devices
+        .try_collect_vec()?
         .into_iter()
-        .map(get_device_info_raw)
+        .map(|path| get_device_info_sysfs(path, true))
         .filter_map(|r| {
             r.map_err(|e| log::trace!("Failed to query device information\n\tbecause {e:?}"))
                 .ok()
@@ -33,25 +76,64 @@ pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Send + Un
     Ok(iter(devices))
 }
 
+pub async fn count() -> HidResult<usize> {
+    match read_dir("/sys/class/hidraw/") {
+        Ok(entries) => Ok(entries.count()),
+        Err(e) => {
+            log::trace!("Failed to read /sys/class/hidraw/, falling back to scanning /dev/\n\tbecause {e:?}");
+            let nodes = read_dir("/dev/")?
+                .filter_map(Result::ok)
+                .filter(|e| e.file_name().to_str().is_some_and(|n| n.starts_with("hidraw")))
+                .count();
+            Ok(nodes)
+        }
+    }
+}
+
+pub async fn enumerate_with_retry(_policy: RetryPolicy) -> HidResult<impl Stream<Item = DeviceInfo> + Send + Unpin> {
+    // sysfs is populated before the device node shows up, so there is nothing to retry
+    enumerate().await
+}
+
 fn get_device_info_raw(path: PathBuf) -> HidResult<Vec<DeviceInfo>> {
+    get_device_info_sysfs(path, false)
+}
+
+/// Retrieves the device information from sysfs, falling back to the device node unless `passive` is set
+fn get_device_info_sysfs(path: PathBuf, passive: bool) -> HidResult<Vec<DeviceInfo>> {
     let properties = read_to_string(path.join("uevent"))?;
     let id = read_property(&properties, "DEVNAME")
-        .ok_or(HidError::custom("Can't find dev name"))
+        .ok_or_else(|| HidError::custom("Can't find dev name"))
         .and_then(mange_dev_name)?;
 
     let properties = read_to_string(path.join("device/uevent"))?;
 
-    let (_bus, vendor_id, product_id) = read_property(&properties, "HID_ID")
-        .and_then(parse_hid_vid_pid)
-        .ok_or(HidError::custom("Can't find hid ids"))?;
-
-    let name = read_property(&properties, "HID_NAME")
-        .ok_or(HidError::custom("Can't find hid name"))?
-        .to_string();
+    let RawInfo {
+        bus_type,
+        vendor_id,
+        product_id,
+        name,
+        physical_path
+    } = RawInfo::from_uevent(&properties, || match passive {
+        true => Err(HidError::custom(format!("Incomplete sysfs information for {id:?}"))),
+        false => {
+            log::trace!("Incomplete sysfs information for {id:?}, querying the device directly");
+            RawInfo::query(&id)
+        }
+    })?;
 
     let serial_number = read_property(&properties, "HID_UNIQ")
         .filter(|s| !s.is_empty())
         .map(str::to_string);
+    // The parent devices of bluetooth or i2c devices may belong to an unrelated usb device, i.e the bluetooth adapter
+    let (manufacturer, interface_class) = match bus_type {
+        BUS_USB => (read_usb_manufacturer(&path), read_usb_interface_class(&path)),
+        _ => (None, None)
+    };
+    // The sysfs device directory is nested below the usb device, hub and host controller it is connected to
+    let topology_path = canonicalize(path.join("device"))
+        .ok()
+        .and_then(|p| p.to_str().map(str::to_string));
 
     let info = DeviceInfo {
         id: id.into(),
@@ -60,22 +142,174 @@ fn get_device_info_raw(path: PathBuf) -> HidResult<Vec<DeviceInfo>> {
         vendor_id,
         usage_id: 0,
         usage_page: 0,
-        private_data: BackendPrivateData { serial_number }
+        collections: Arc::new([(0, 0)]),
+        private_data: BackendPrivateData {
+            serial_number,
+            manufacturer,
+            bus_type,
+            physical_path,
+            topology_path,
+            interface_class
+        }
     };
 
-    let results = HidrawReportDescriptor::from_syspath(&path)
-        .map(|descriptor| {
-            descriptor
-                .usages()
-                .map(|(usage_page, usage_id)| DeviceInfo {
-                    usage_page,
-                    usage_id,
-                    ..info.clone()
+    Ok(expand_usages(info, HidrawReportDescriptor::from_syspath(&path)))
+}
+
+/// Retrieves the device information directly from a device node without relying on sysfs
+fn get_device_info_from_node(id: PathBuf) -> HidResult<Vec<DeviceInfo>> {
+    let fd = open_node(&id)?;
+    let raw = RawInfo::from_fd(&fd)?;
+    let descriptor = HidrawReportDescriptor::from_fd(&fd);
+
+    let info = DeviceInfo {
+        id: id.into(),
+        name: raw.name,
+        product_id: raw.product_id,
+        vendor_id: raw.vendor_id,
+        usage_id: 0,
+        usage_page: 0,
+        collections: Arc::new([(0, 0)]),
+        private_data: BackendPrivateData {
+            serial_number: None,
+            manufacturer: None,
+            bus_type: raw.bus_type,
+            physical_path: raw.physical_path,
+            topology_path: None,
+            interface_class: None
+        }
+    };
+
+    Ok(expand_usages(info, descriptor))
+}
+
+/// Reads the manufacturer string of the usb device that the hidraw device belongs to
+fn read_usb_manufacturer(syspath: &Path) -> Option<String> {
+    let device = canonicalize(syspath.join("device")).ok()?;
+    let manufacturer = device
+        .ancestors()
+        .find_map(|p| read_to_string(p.join("manufacturer")).ok())?;
+    Some(manufacturer.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+/// Reads the subclass and protocol of the usb interface that the hidraw device belongs to
+fn read_usb_interface_class(syspath: &Path) -> Option<(u8, u8)> {
+    let device = canonicalize(syspath.join("device")).ok()?;
+    let interface = device.ancestors().find(|p| p.join("bInterfaceProtocol").exists())?;
+    let read = |name: &str| {
+        read_to_string(interface.join(name))
+            .ok()
+            .and_then(|value| u8::from_str_radix(value.trim(), 16).ok())
+    };
+    Some((read("bInterfaceSubClass")?, read("bInterfaceProtocol")?))
+}
+
+/// Creates one [DeviceInfo] per top-level usage of the device
+///
+/// Devices whose usages can't be determined are kept with usage page and usage id `0`.
+fn expand_usages(info: DeviceInfo, descriptor: HidResult<HidrawReportDescriptor>) -> Vec<DeviceInfo> {
+    let descriptor = match descriptor {
+        Ok(descriptor) => descriptor,
+        Err(e) => {
+            log::trace!("Defaulting the usage of {:?} to 0\n\tbecause the report descriptor is not available: {e:?}", info.name);
+            return vec![info];
+        }
+    };
+    let collections: Arc<[(u16, u16)]> = descriptor.usages().collect();
+    if collections.is_empty() {
+        log::trace!("Defaulting the usage of {:?} to 0\n\tbecause the report descriptor declares no top-level usage", info.name);
+        return vec![info];
+    }
+    collections
+        .iter()
+        .map(|&(usage_page, usage_id)| DeviceInfo {
+            usage_page,
+            usage_id,
+            collections: collections.clone(),
+            ..info.clone()
+        })
+        .collect()
+}
+
+fn open_node(id: &Path) -> HidResult<OwnedFd> {
+    let fd = OpenOptions::new()
+        .read(true)
+        .custom_flags((OFlag::O_CLOEXEC | OFlag::O_NONBLOCK).bits())
+        .open(id)?
+        .into();
+    Ok(fd)
+}
+
+/// Device information as reported by the hidraw ioctls of an opened device node
+struct RawInfo {
+    bus_type: u16,
+    vendor_id: u16,
+    product_id: u16,
+    name: String,
+    physical_path: Option<String>
+}
+
+impl RawInfo {
+    /// Reads the device information from the uevent properties of the hid device
+    ///
+    /// The device node is only queried if the ids or the name are missing, the physical path from sysfs takes precedence.
+    fn from_uevent(properties: &str, query: impl FnOnce() -> HidResult<Self>) -> HidResult<Self> {
+        let ids = read_property(properties, "HID_ID").and_then(parse_hid_vid_pid);
+        let name = read_property(properties, "HID_NAME");
+        let physical_path = read_property(properties, "HID_PHYS")
+            .filter(|s| !s.is_empty())
+            .map(str::to_string);
+
+        match (ids, name) {
+            (Some((bus_type, vendor_id, product_id)), Some(name)) => Ok(Self {
+                bus_type,
+                vendor_id,
+                product_id,
+                name: name.to_string(),
+                physical_path
+            }),
+            _ => {
+                let raw = query()?;
+                Ok(Self {
+                    physical_path: physical_path.or(raw.physical_path),
+                    ..raw
                 })
-                .collect()
+            }
+        }
+    }
+
+    fn query(id: &Path) -> HidResult<Self> {
+        Self::from_fd(&open_node(id)?)
+    }
+
+    fn from_fd(fd: &OwnedFd) -> HidResult<Self> {
+        let mut info = HidrawDevInfo::default();
+        unsafe { hidraw_ioc_grawinfo(fd.as_raw_fd(), &mut info) }.map_err(BackendError::from)?;
+
+        let name = read_ioctl_string(fd, hidraw_ioc_grawname)?;
+        let physical_path = read_ioctl_string(fd, hidraw_ioc_grawphys)
+            .map_err(|e| log::trace!("Failed to query physical path\n\tbecause {e:?}"))
+            .ok()
+            .filter(|s| !s.is_empty());
+
+        Ok(Self {
+            bus_type: info.bustype as u16,
+            vendor_id: info.vendor as u16,
+            product_id: info.product as u16,
+            name,
+            physical_path
         })
-        .unwrap_or_else(|_| vec![info]);
-    Ok(results)
+    }
+}
+
+fn read_ioctl_string(fd: &OwnedFd, ioctl: unsafe fn(c_int, &mut [u8]) -> nix::Result<c_int>) -> HidResult<String> {
+    let mut buffer = [0u8; 256];
+    let len = unsafe { ioctl(fd.as_raw_fd(), &mut buffer) }.map_err(BackendError::from)? as usize;
+    let string = buffer[..len.min(buffer.len())]
+        .split(|c| *c == 0x0)
+        .next()
+        .unwrap_or_default();
+    Ok(String::from_utf8_lossy(string).into_owned())
 }
 
 fn read_property<'a>(properties: &'a str, key: &str) -> Option<&'a str> {
@@ -109,6 +343,38 @@ fn parse_hid_vid_pid(s: &str) -> Option<(u16, u16, u16)> {
     Some((devtype, vendor, product))
 }
 
+impl DeviceInfo {
+    /// The physical path reported by the kernel, i.e `usb-0000:00:14.0-1/input0`
+    pub(crate) fn physical_location(&self) -> Option<String> {
+        self.private_data.physical_path.clone()
+    }
+
+    /// The sysfs path of the device, i.e `/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2.3/1-2.3:1.0/0003:046D:C52B.0001`
+    pub(crate) fn topology(&self) -> Option<String> {
+        self.private_data.topology_path.clone()
+    }
+
+    /// Read from the `bInterfaceSubClass` and `bInterfaceProtocol` attributes of the usb interface in sysfs
+    pub(crate) fn interface_class(&self) -> Option<(u8, u8)> {
+        self.private_data.interface_class
+    }
+
+    pub(crate) fn connected_bus(&self) -> BusType {
+        bus_from_linux(self.private_data.bus_type)
+    }
+}
+
+fn bus_from_linux(bus_type: u16) -> BusType {
+    match bus_type {
+        BUS_USB => BusType::Usb,
+        BUS_BLUETOOTH => BusType::Bluetooth,
+        BUS_VIRTUAL => BusType::Virtual,
+        BUS_I2C => BusType::I2c,
+        BUS_SPI => BusType::Spi,
+        _ => BusType::Unknown
+    }
+}
+
 impl SerialNumberExt for DeviceInfo {
     fn serial_number(&self) -> Option<&str> {
         self.private_data
@@ -118,6 +384,22 @@ impl SerialNumberExt for DeviceInfo {
     }
 }
 
+impl ManufacturerExt for DeviceInfo {
+    fn manufacturer(&self) -> Option<&str> {
+        self.private_data.manufacturer.as_deref()
+    }
+}
+
+impl HidrawDeviceInfoExt for DeviceInfo {
+    fn bus_type(&self) -> u16 {
+        self.private_data.bus_type
+    }
+
+    fn physical_path(&self) -> Option<&str> {
+        self.private_data.physical_path.as_deref()
+    }
+}
+
 
 #[derive(Debug)]
 pub struct BackendDevice {
@@ -131,15 +413,137 @@ impl BackendDevice {
             .map_err(HidError::from)
     }
 
+    pub fn try_read_input_report(&self, buf: &mut [u8]) -> HidResult<Option<usize>> {
+        // The node is always opened with O_NONBLOCK, so this never blocks
+        match read(self.fd.get_ref().as_raw_fd(), buf) {
+            Ok(size) => Ok(Some(size)),
+            Err(Errno::EAGAIN) => Ok(None),
+            Err(err) => Err(BackendError::from(err).into())
+        }
+    }
+
+    pub async fn disconnected(&self) -> HidResult<()> {
+        // An epoll instance that watches the node without any events only becomes readable once the node signals POLLHUP or POLLERR,
+        // which hidraw does after the device got removed. Waiting for the node itself would wake up for every input report
+        let epoll = Epoll::new(EpollCreateFlags::EPOLL_CLOEXEC).map_err(BackendError::from)?;
+        epoll
+            .add(self.fd.get_ref(), EpollEvent::new(EpollFlags::empty(), 0))
+            .map_err(BackendError::from)?;
+        let epoll = AsyncFd::new(epoll.0)?;
+        read_with(&epoll, |_| match self.hung_up() {
+            true => Ok(()),
+            false => Err(ErrorKind::WouldBlock.into())
+        })
+        .await?;
+        Ok(())
+    }
+
+    fn hung_up(&self) -> bool {
+        // Polling without events doesn't consume any input report
+        let mut fds = [PollFd::new(self.fd.get_ref(), PollFlags::empty())];
+        match poll(&mut fds, 0) {
+            Ok(_) => fds[0]
+                .revents()
+                .is_some_and(|events| events.intersects(PollFlags::POLLHUP | PollFlags::POLLERR)),
+            Err(e) => {
+                log::trace!("Failed to poll the device node\n\tbecause {e:?}");
+                false
+            }
+        }
+    }
+
+    pub fn dropped_reports(&self) -> u64 {
+        // The kernel silently discards the oldest report once the hidraw ring buffer is full
+        0
+    }
+
     pub async fn write_output_report(&self, data: &[u8]) -> HidResult<()> {
         ensure!(!data.is_empty(), HidError::zero_sized_data());
-        write_with(&self.fd, |fd| write(fd.as_raw_fd(), data).map_err(BackendError::from))
-            .await
+        match write_with(&self.fd, |fd| write(fd.as_raw_fd(), data).map_err(BackendError::from)).await {
+            Ok(size) => {
+                debug_assert_eq!(size, data.len());
+                Ok(())
+            }
+            // The size limit depends on the kernel version (HID_MAX_BUFFER_SIZE) and the driver, so it is left to the kernel to check it
+            Err(e) if e.raw_os_error() == Some(Errno::EINVAL as i32) => Err(HidError::custom(format!(
+                "The kernel rejected the output report of {} bytes, it may exceed the transfer limit of the kernel or driver",
+                data.len()
+            ))),
+            Err(e) => Err(e.into())
+        }
+    }
+
+    pub async fn set_output_report(&self, data: &[u8]) -> HidResult<()> {
+        ensure!(!data.is_empty(), HidError::zero_sized_data());
+        let mut buffer = data.to_vec();
+        // HIDIOCSOUTPUT was added in Linux 5.11, older kernels reject it with EINVAL
+        match unsafe { hidraw_ioc_set_output(self.fd.as_raw_fd(), &mut buffer) } {
+            Ok(size) => {
+                debug_assert_eq!(size as usize, data.len());
+                Ok(())
+            },
+            Err(Errno::EINVAL | Errno::ENOTTY) => Err(HidError::unsupported()),
+            Err(err) => Err(BackendError::from(err).into())
+        }
+    }
+
+    pub async fn flush(&self) -> HidResult<()> {
+        // write(2) on a hidraw node only returns once the report was handed to the device
+        Ok(())
+    }
+
+    pub async fn send_feature_report(&self, data: &[u8]) -> HidResult<()> {
+        ensure!(!data.is_empty(), HidError::zero_sized_data());
+        let mut buffer = data.to_vec();
+        unsafe { hidraw_ioc_set_feature(self.fd.as_raw_fd(), &mut buffer) }
+            .map_err(BackendError::from)
             .map_err(HidError::from)
-            .map(|i| debug_assert_eq!(i, data.len()))
+            .map(|i| debug_assert_eq!(i as usize, data.len()))
+    }
+
+    pub async fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        unsafe { hidraw_ioc_get_feature(self.fd.as_raw_fd(), buf) }
+            .map_err(BackendError::from)
+            .map_err(HidError::from)
+            .map(|i| i as usize)
+    }
+
+    pub async fn get_output_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        // HIDIOCGOUTPUT was added in Linux 5.11, older kernels reject it with EINVAL
+        match unsafe { hidraw_ioc_get_output(self.fd.as_raw_fd(), buf) } {
+            Ok(size) => Ok(size as usize),
+            Err(Errno::EINVAL | Errno::ENOTTY) => Err(HidError::unsupported()),
+            Err(err) => Err(BackendError::from(err).into())
+        }
+    }
+
+    pub async fn physical_descriptor(&self) -> HidResult<Option<Vec<u8>>> {
+        // The descriptor can only be requested through usbfs, which claims the interface for requests to it.
+        // That fails with EBUSY as long as usbhid is bound to the interface, and it always is while the hidraw node exists
+        Err(HidError::unsupported())
+    }
+
+    pub async fn report_ids(&self) -> HidResult<ReportIds> {
+        let descriptor = HidrawReportDescriptor::from_fd(self.fd.get_ref())?;
+        Ok(ReportIds::from_descriptor(descriptor.as_bytes()))
+    }
+
+    pub async fn report_descriptor(&self) -> HidResult<Vec<u8>> {
+        let descriptor = HidrawReportDescriptor::from_fd(self.fd.get_ref())?;
+        Ok(descriptor.as_bytes().to_vec())
     }
 }
 
+// From linux/input.h
+const BUS_USB: u16 = 0x03;
+const BUS_BLUETOOTH: u16 = 0x05;
+const BUS_VIRTUAL: u16 = 0x06;
+const BUS_I2C: u16 = 0x18;
+const BUS_SPI: u16 = 0x1c;
+
+
 pub async fn open(id: &BackendDeviceId, mode: AccessMode) -> HidResult<BackendDevice> {
     let fd: OwnedFd = OpenOptions::new()
         .read(mode.readable())
@@ -155,11 +559,57 @@ pub async fn open(id: &BackendDeviceId, mode: AccessMode) -> HidResult<BackendDe
     Ok(BackendDevice { fd: AsyncFd::new(fd)? })
 }
 
+pub fn string_descriptor(id: &BackendDeviceId, index: u8) -> HidResult<Option<String>> {
+    // hidraw only exposes the manufacturer, product and serial number, other strings are requested from the parent usb device
+    let interface = UsbInterface::from_hidraw(&open_node(id)?)?.ok_or_else(|| HidError::unsupported())?;
+    interface.get_string(index)
+}
+
+pub fn is_claimed(id: &BackendDeviceId) -> bool {
+    bound_to_input_driver(id) || opened_by_other_process(id)
+}
+
+/// Checks if the kernel turned the device into input devices, which consume the reports as well
+fn bound_to_input_driver(id: &Path) -> bool {
+    id.file_name()
+        .map(|name| Path::new("/sys/class/hidraw/").join(name).join("device/input"))
+        .and_then(|path| read_dir(path).ok())
+        .is_some_and(|mut entries| entries.next().is_some())
+}
+
+/// Checks the open file descriptors of all processes that are visible to us
+fn opened_by_other_process(id: &Path) -> bool {
+    let own_pid = std::process::id().to_string();
+    let Ok(processes) = read_dir("/proc/") else {
+        return false;
+    };
+    processes
+        .filter_map(Result::ok)
+        .filter(|p| {
+            p.file_name()
+                .to_str()
+                .is_some_and(|n| n != own_pid && n.bytes().all(|b| b.is_ascii_digit()))
+        })
+        .filter_map(|p| read_dir(p.path().join("fd")).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .any(|fd| read_link(fd.path()).is_ok_and(|target| target == id))
+}
+
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct BackendPrivateData {
-    serial_number: Option<String>
+    serial_number: Option<String>,
+    manufacturer: Option<String>,
+    bus_type: u16,
+    physical_path: Option<String>,
+    topology_path: Option<String>,
+    interface_class: Option<(u8, u8)>
 }
+
+/// write(2) either sends the whole report while the future is polled or nothing at all
+pub const DROPPED_WRITES_CONTINUE: bool = false;
+
 pub type BackendDeviceId = PathBuf;
 pub type BackendError = std::io::Error;
 
@@ -169,6 +619,11 @@ impl From<BackendError> for ErrorSource {
     }
 }
 
+pub fn is_disconnected(error: &BackendError) -> bool {
+    // hidraw fails reads with EIO and writes with ENODEV once the device is gone
+    matches!(error.raw_os_error().map(Errno::from_i32), Some(Errno::EIO | Errno::ENODEV))
+}
+
 #[cfg(all(feature = "async-io", feature = "tokio"))]
 compile_error!("Only tokio or async-io can be active at the same time");
 
@@ -227,22 +682,22 @@ pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo>> {
 fn get_device_info(raw_device: Device) -> HidResult<Vec<DeviceInfo>> {
   let device = raw_device
       .parent_with_subsystem("hid")?
-      .ok_or(HidError::custom("Can't find hid interface"))?;
+      .ok_or_else(|| HidError::custom("Can't find hid interface"))?;
 
   let (_bus, vendor_id, product_id) = device
       .property_value("HID_ID")
       .and_then(|s| s.to_str())
       .and_then(parse_hid_vid_pid)
-      .ok_or(HidError::custom("Can't find hid ids"))?;
+      .ok_or_else(|| HidError::custom("Can't find hid ids"))?;
 
   let id = raw_device
       .devnode()
-      .ok_or(HidError::custom("Can't find device node"))?
+      .ok_or_else(|| HidError::custom("Can't find device node"))?
       .to_path_buf();
 
   let name = device
       .property_value("HID_NAME")
-      .ok_or(HidError::custom("Can't find hid name"))?
+      .ok_or_else(|| HidError::custom("Can't find hid name"))?
       .to_string_lossy()
       .to_string();
 
@@ -0,0 +1,121 @@
+//! Tests of the hidraw backend
+//!
+//! The tests against virtual devices require write access to `/dev/uhid` and are therefore ignored by default.
+
+use std::thread::sleep;
+use std::time::Duration;
+
+use futures_lite::future::block_on;
+
+use super::{bus_from_linux, open_node, RawInfo};
+use crate::{BusType, Device, HidError, VirtualDevice, VirtualDeviceInfo};
+
+const VENDOR_ID: u16 = 0x1209;
+const PRODUCT_ID: u16 = 0x0001;
+
+/// A vendor defined device with a 64 byte input and output report
+const DESCRIPTOR: &[u8] = &[
+    0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+    0x09, 0x01, // Usage (0x01)
+    0xa1, 0x01, // Collection (Application)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x26, 0xff, 0x00, //   Logical Maximum (255)
+    0x75, 0x08, //   Report Size (8)
+    0x95, 0x40, //   Report Count (64)
+    0x09, 0x02, //   Usage (0x02)
+    0x81, 0x02, //   Input (Data, Variable, Absolute)
+    0x09, 0x03, //   Usage (0x03)
+    0x91, 0x02, //   Output (Data, Variable, Absolute)
+    0xc0 // End Collection
+];
+
+fn virtual_device_info(product_id: u16) -> VirtualDeviceInfo {
+    VirtualDeviceInfo {
+        name: "async-hid test device".into(),
+        vendor_id: VENDOR_ID,
+        product_id,
+        ..Default::default()
+    }
+}
+
+/// Creates a virtual device and opens its hidraw node once it shows up
+fn open_virtual_device(info: VirtualDeviceInfo) -> (VirtualDevice, Device) {
+    let (vendor_id, product_id) = (info.vendor_id, info.product_id);
+    let virtual_device = VirtualDevice::create(DESCRIPTOR, info).expect("Failed to create the virtual device");
+    for _ in 0..50 {
+        if let Ok(device) = block_on(Device::open_first_matching(vendor_id, product_id)) {
+            return (virtual_device, device);
+        }
+        sleep(Duration::from_millis(20));
+    }
+    panic!("The hidraw node of the virtual device didn't show up");
+}
+
+#[test]
+#[ignore = "requires write access to /dev/uhid"]
+fn output_report_at_transfer_limit() {
+    let (_virtual_device, device) = open_virtual_device(virtual_device_info(PRODUCT_ID));
+    // uhid accepts at most UHID_DATA_MAX (4096) bytes, which is also the lowest HID_MAX_BUFFER_SIZE of any kernel
+    block_on(device.write_output_report(&[0u8; 4096])).expect("A report at the limit must be accepted");
+    let error = block_on(device.write_output_report(&[0u8; 4097])).expect_err("A report above the limit must be rejected");
+    assert!(error.to_string().contains("4097 bytes"), "{error}");
+}
+
+#[test]
+#[ignore = "requires write access to /dev/uhid"]
+fn raw_info_of_virtual_device() {
+    let info = VirtualDeviceInfo {
+        phys: "async-hid/test0".into(),
+        bus_type: 0x03,
+        ..virtual_device_info(PRODUCT_ID + 1)
+    };
+    let (_virtual_device, device) = open_virtual_device(info.clone());
+    let raw = RawInfo::from_fd(&open_node(&device.info().id.0).unwrap()).unwrap();
+    assert_eq!((raw.bus_type, raw.vendor_id, raw.product_id), (info.bus_type, info.vendor_id, info.product_id));
+    assert_eq!(raw.name, info.name);
+    assert_eq!(raw.physical_path, Some(info.phys));
+}
+
+fn raw_info() -> RawInfo {
+    RawInfo {
+        bus_type: 0x05,
+        vendor_id: 0x1234,
+        product_id: 0x5678,
+        name: "raw name".into(),
+        physical_path: Some("raw/phys".into())
+    }
+}
+
+#[test]
+fn complete_uevent_skips_raw_info() {
+    let properties = "HID_ID=0003:0000046D:0000C52B\nHID_NAME=Logitech USB Receiver\nHID_PHYS=usb-0000:00:14.0-1/input2\n";
+    let info = RawInfo::from_uevent(properties, || panic!("The device node must not be queried")).unwrap();
+    assert_eq!((info.bus_type, info.vendor_id, info.product_id), (0x03, 0x046d, 0xc52b));
+    assert_eq!(info.name, "Logitech USB Receiver");
+    assert_eq!(info.physical_path.as_deref(), Some("usb-0000:00:14.0-1/input2"));
+}
+
+#[test]
+fn incomplete_uevent_falls_back_to_raw_info() {
+    // The physical path from sysfs is kept, everything else comes from the device node
+    let info = RawInfo::from_uevent("HID_ID=0003:0000046D:0000C52B\nHID_PHYS=sysfs/phys\n", || Ok(raw_info())).unwrap();
+    assert_eq!((info.bus_type, info.vendor_id, info.product_id), (0x05, 0x1234, 0x5678));
+    assert_eq!(info.name, "raw name");
+    assert_eq!(info.physical_path.as_deref(), Some("sysfs/phys"));
+
+    // An empty physical path counts as missing
+    let info = RawInfo::from_uevent("HID_NAME=sysfs name\nHID_PHYS=\n", || Ok(raw_info())).unwrap();
+    assert_eq!(info.physical_path.as_deref(), Some("raw/phys"));
+
+    assert!(RawInfo::from_uevent("", || Err(HidError::not_found())).is_err());
+}
+
+#[test]
+fn linux_bus_types() {
+    assert_eq!(bus_from_linux(0x03), BusType::Usb);
+    assert_eq!(bus_from_linux(0x05), BusType::Bluetooth);
+    assert_eq!(bus_from_linux(0x06), BusType::Virtual);
+    assert_eq!(bus_from_linux(0x18), BusType::I2c);
+    assert_eq!(bus_from_linux(0x1c), BusType::Spi);
+    assert_eq!(bus_from_linux(0x19), BusType::Unknown);
+}
@@ -0,0 +1,163 @@
+//! Virtual devices backed by the uhid interface of the kernel (see Documentation/hid/uhid.rst)
+
+use std::fs::OpenOptions;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::os::unix::fs::OpenOptionsExt;
+
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::unistd::{read, write};
+
+use crate::backend::hidraw::async_api::{read_with, write_with, AsyncFd};
+use crate::backend::BackendError;
+use crate::{ensure, HidError, HidResult};
+
+// From linux/uhid.h
+const UHID_DESTROY: u32 = 1;
+const UHID_OUTPUT: u32 = 6;
+const UHID_GET_REPORT: u32 = 9;
+const UHID_GET_REPORT_REPLY: u32 = 10;
+const UHID_CREATE2: u32 = 11;
+const UHID_INPUT2: u32 = 12;
+const UHID_SET_REPORT: u32 = 13;
+const UHID_SET_REPORT_REPLY: u32 = 14;
+
+const UHID_DATA_MAX: usize = 4096;
+/// The size of the packed `struct uhid_event`, which is dominated by `struct uhid_create2_req`
+const UHID_EVENT_SIZE: usize = 4 + 128 + 64 + 64 + 2 + 2 + 4 + 4 + 4 + 4 + UHID_DATA_MAX;
+
+// From linux/input.h
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// The information that a [VirtualDevice] presents to the system
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VirtualDeviceInfo {
+    /// The human readable name (truncated to 127 bytes)
+    pub name: String,
+    /// The physical path, which is usually empty for virtual devices (truncated to 63 bytes)
+    pub phys: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub version: u32,
+    /// The bus type reported to the kernel (i.e `BUS_USB` = 0x03, `BUS_VIRTUAL` = 0x06)
+    pub bus_type: u16,
+    pub country: u32
+}
+
+impl Default for VirtualDeviceInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            phys: String::new(),
+            vendor_id: 0,
+            product_id: 0,
+            version: 0,
+            bus_type: BUS_VIRTUAL,
+            country: 0
+        }
+    }
+}
+
+/// A virtual HID device created through uhid
+///
+/// The device shows up like any other HID device (including a hidraw node) until this struct is dropped.
+pub struct VirtualDevice {
+    fd: AsyncFd
+}
+
+impl VirtualDevice {
+    /// Creates a new virtual device with the given report descriptor
+    ///
+    /// This requires write access to `/dev/uhid`, which usually means root privileges.
+    pub fn create(descriptor: &[u8], info: VirtualDeviceInfo) -> HidResult<Self> {
+        ensure!(!descriptor.is_empty(), HidError::zero_sized_data());
+        ensure!(descriptor.len() <= UHID_DATA_MAX, HidError::custom("Report descriptor is too large"));
+        let fd: OwnedFd = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags((OFlag::O_CLOEXEC | OFlag::O_NONBLOCK).bits())
+            .open("/dev/uhid")?
+            .into();
+
+        let mut event = new_event(UHID_CREATE2);
+        let name = info.name.as_bytes();
+        let name = &name[..name.len().min(127)];
+        event[4..4 + name.len()].copy_from_slice(name);
+        let phys = info.phys.as_bytes();
+        let phys = &phys[..phys.len().min(63)];
+        event[132..132 + phys.len()].copy_from_slice(phys);
+        event[260..262].copy_from_slice(&(descriptor.len() as u16).to_ne_bytes());
+        event[262..264].copy_from_slice(&info.bus_type.to_ne_bytes());
+        event[264..268].copy_from_slice(&(info.vendor_id as u32).to_ne_bytes());
+        event[268..272].copy_from_slice(&(info.product_id as u32).to_ne_bytes());
+        event[272..276].copy_from_slice(&info.version.to_ne_bytes());
+        event[276..280].copy_from_slice(&info.country.to_ne_bytes());
+        event[280..280 + descriptor.len()].copy_from_slice(descriptor);
+        write(fd.as_raw_fd(), &event).map_err(BackendError::from)?;
+
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    /// Sends an input report to the system, as if the device had produced it
+    ///
+    /// The first byte must contain the report id if the descriptor declares numbered reports
+    pub async fn write_input_report(&self, data: &[u8]) -> HidResult<()> {
+        ensure!(!data.is_empty(), HidError::zero_sized_data());
+        ensure!(data.len() <= UHID_DATA_MAX, HidError::custom("Input report is too large"));
+        let mut event = new_event(UHID_INPUT2);
+        event[4..6].copy_from_slice(&(data.len() as u16).to_ne_bytes());
+        event[6..6 + data.len()].copy_from_slice(data);
+        self.write_event(&event).await
+    }
+
+    /// Waits for the next output report that the system sends to this device
+    ///
+    /// Feature report requests are rejected with `EIO` while waiting, as they can't be answered through this api.
+    pub async fn read_output_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let mut event = new_event(0);
+        loop {
+            read_with(&self.fd, |fd| read(fd.as_raw_fd(), &mut event).map_err(BackendError::from)).await?;
+            let id = &event[4..8];
+            match u32::from_ne_bytes(event[..4].try_into().unwrap()) {
+                UHID_OUTPUT => {
+                    let size = (u16::from_ne_bytes(event[4100..4102].try_into().unwrap()) as usize).min(UHID_DATA_MAX);
+                    let size = size.min(buf.len());
+                    buf[..size].copy_from_slice(&event[4..4 + size]);
+                    return Ok(size);
+                }
+                UHID_GET_REPORT => self.reject_request(UHID_GET_REPORT_REPLY, id.try_into().unwrap()).await?,
+                UHID_SET_REPORT => self.reject_request(UHID_SET_REPORT_REPLY, id.try_into().unwrap()).await?,
+                other => log::trace!("Ignoring uhid event of type {}", other)
+            }
+        }
+    }
+
+    async fn reject_request(&self, reply: u32, id: [u8; 4]) -> HidResult<()> {
+        let mut event = new_event(reply);
+        event[4..8].copy_from_slice(&id);
+        event[8..10].copy_from_slice(&(Errno::EIO as u16).to_ne_bytes());
+        self.write_event(&event).await
+    }
+
+    async fn write_event(&self, event: &[u8]) -> HidResult<()> {
+        write_with(&self.fd, |fd| write(fd.as_raw_fd(), event).map_err(BackendError::from))
+            .await
+            .map_err(HidError::from)
+            .map(|i| debug_assert_eq!(i, event.len()))
+    }
+}
+
+impl Drop for VirtualDevice {
+    fn drop(&mut self) {
+        // Closing the fd destroys the device as well, this just makes it explicit
+        if let Err(e) = write(self.fd.get_ref().as_raw_fd(), &new_event(UHID_DESTROY)) {
+            log::trace!("Failed to destroy uhid device\n\tbecause {e:?}");
+        }
+    }
+}
+
+fn new_event(kind: u32) -> Vec<u8> {
+    let mut event = vec![0u8; UHID_EVENT_SIZE];
+    event[..4].copy_from_slice(&kind.to_ne_bytes());
+    event
+}
@@ -0,0 +1,132 @@
+//! Access to the usb device behind a hidraw node through usbfs
+
+use std::ffi::c_void;
+use std::fs::{canonicalize, read_to_string, OpenOptions};
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use nix::errno::Errno;
+use nix::fcntl::OFlag;
+use nix::ioctl_readwrite;
+use nix::sys::stat::{fstat, major, minor};
+
+use crate::backend::BackendError;
+use crate::{HidError, HidResult};
+
+// From linux/usbdevice_fs.h
+const USBDEVFS_IOC_MAGIC: u8 = b'U';
+const USBDEVFS_CONTROL: u8 = 0;
+
+/// Mirrors `struct usbdevfs_ctrltransfer` from linux/usbdevice_fs.h
+#[repr(C)]
+struct UsbdevfsCtrlTransfer {
+    request_type: u8,
+    request: u8,
+    value: u16,
+    index: u16,
+    length: u16,
+    timeout: u32,
+    data: *mut c_void
+}
+
+ioctl_readwrite!(usbdevfs_control, USBDEVFS_IOC_MAGIC, USBDEVFS_CONTROL, UsbdevfsCtrlTransfer);
+
+// From linux/usb/ch9.h
+const USB_DIR_IN_STANDARD_DEVICE: u8 = 0x80;
+const USB_REQ_GET_DESCRIPTOR: u8 = 0x06;
+const USB_DT_STRING: u8 = 0x03;
+
+const CONTROL_TIMEOUT_MS: u32 = 1000;
+
+/// The usb interface that a hidraw device belongs to
+#[derive(Debug)]
+pub struct UsbInterface {
+    /// The usbfs node of the usb device (i.e `/dev/bus/usb/001/004`)
+    device_node: PathBuf
+}
+
+impl UsbInterface {
+    /// Locates the usb interface of an opened hidraw device
+    ///
+    /// Returns `None` if the device is not connected over usb (i.e bluetooth or i2c devices)
+    pub fn from_hidraw(fd: &impl AsRawFd) -> HidResult<Option<Self>> {
+        let stat = fstat(fd.as_raw_fd()).map_err(BackendError::from)?;
+        let syspath = canonicalize(format!("/sys/dev/char/{}:{}", major(stat.st_rdev), minor(stat.st_rdev)))?;
+
+        let Some(interface) = syspath.ancestors().find(|p| p.join("bInterfaceNumber").exists()) else {
+            return Ok(None);
+        };
+        let device = interface
+            .parent()
+            .ok_or_else(|| HidError::custom("Usb interface has no parent device"))?;
+
+        let bus_number = read_dec_attribute(device, "busnum")?;
+        let device_number = read_dec_attribute(device, "devnum")?;
+
+        Ok(Some(Self {
+            device_node: PathBuf::from(format!("/dev/bus/usb/{:03}/{:03}", bus_number, device_number))
+        }))
+    }
+
+    /// Requests a string descriptor of the usb device in the first language that the device supports
+    ///
+    /// Returns `None` if the string is empty or the device stalled the request
+    pub fn get_string(&self, index: u8) -> HidResult<Option<String>> {
+        // String descriptor 0 contains the supported language ids
+        let mut languages = [0u8; 4];
+        let value = (USB_DT_STRING as u16) << 8;
+        if self.control_transfer(USB_DIR_IN_STANDARD_DEVICE, USB_REQ_GET_DESCRIPTOR, value, 0, &mut languages)? != Some(languages.len()) {
+            return Ok(None);
+        }
+        let language = u16::from_le_bytes([languages[2], languages[3]]);
+
+        let mut descriptor = [0u8; 255];
+        let Some(len) = self.control_transfer(USB_DIR_IN_STANDARD_DEVICE, USB_REQ_GET_DESCRIPTOR, value | index as u16, language, &mut descriptor)? else {
+            return Ok(None);
+        };
+        // The first byte contains the length of the descriptor, the second one the descriptor type
+        let len = len.min(descriptor[0] as usize).max(2);
+        let string: Vec<u16> = descriptor[2..len]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        let string = String::from_utf16_lossy(&string);
+        Ok((!string.is_empty()).then_some(string))
+    }
+
+    /// Performs a control transfer on the usb device
+    ///
+    /// Returns `None` if the device stalled the request, which usually means that it is not supported
+    pub fn control_transfer(&self, request_type: u8, request: u8, value: u16, index: u16, data: &mut [u8]) -> HidResult<Option<usize>> {
+        let fd: OwnedFd = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(OFlag::O_CLOEXEC.bits())
+            .open(&self.device_node)?
+            .into();
+
+        let mut transfer = UsbdevfsCtrlTransfer {
+            request_type,
+            request,
+            value,
+            index,
+            length: data.len().min(u16::MAX as usize) as u16,
+            timeout: CONTROL_TIMEOUT_MS,
+            data: data.as_mut_ptr() as _
+        };
+        match unsafe { usbdevfs_control(fd.as_raw_fd(), &mut transfer) } {
+            Ok(len) => Ok(Some(len as usize)),
+            Err(Errno::EPIPE) => Ok(None),
+            Err(err) => Err(BackendError::from(err).into())
+        }
+    }
+}
+
+fn read_dec_attribute(path: &Path, name: &str) -> HidResult<u32> {
+    let value = read_to_string(path.join(name))?;
+    value
+        .trim()
+        .parse()
+        .map_err(|_| HidError::custom(format!("Invalid value for {name}")))
+}
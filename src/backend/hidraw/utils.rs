@@ -40,3 +40,23 @@ impl<T, E, I: Iterator<Item = Result<T, E>>> TryIterExt<T, E> for I {
         Ok(result)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use futures_lite::future::block_on;
+    use futures_lite::StreamExt;
+
+    use super::*;
+
+    #[test]
+    fn dropped_stream_skips_remaining_items() {
+        // The enumeration queries every device inside of the iterator, so dropping the stream must not evaluate the rest
+        let queried = Cell::new(0);
+        let mut devices = iter((0..3).inspect(|_| queried.set(queried.get() + 1)));
+        assert_eq!(block_on(devices.next()), Some(0));
+        drop(devices);
+        assert_eq!(queried.get(), 1);
+    }
+}
@@ -3,15 +3,21 @@ use std::mem::ManuallyDrop;
 use std::ptr::null_mut;
 use std::slice::from_raw_parts;
 
+use core_foundation::array::CFArray;
 use core_foundation::base::{kCFAllocatorDefault, CFIndex, CFRelease, CFType, TCFType};
+use core_foundation::dictionary::CFDictionary;
 use core_foundation::number::CFNumber;
 use core_foundation::runloop::CFRunLoop;
 use core_foundation::string::CFString;
 use core_foundation::{impl_TCFType, ConcreteCFType};
-use io_kit_sys::hid::base::{IOHIDDeviceRef, IOHIDReportCallback};
-use io_kit_sys::hid::device::{IOHIDDeviceClose, IOHIDDeviceCreate, IOHIDDeviceGetProperty, IOHIDDeviceGetTypeID, IOHIDDeviceOpen, IOHIDDeviceScheduleWithRunLoop, IOHIDDeviceSetReport, IOHIDDeviceUnscheduleFromRunLoop};
-use io_kit_sys::hid::keys::{kIOHIDMaxInputReportSizeKey, IOHIDReportType};
-use io_kit_sys::ret::{kIOReturnSuccess, IOReturn};
+use io_kit_sys::hid::base::{IOHIDCallback, IOHIDDeviceRef, IOHIDElementRef, IOHIDReportCallback};
+use io_kit_sys::hid::device::{IOHIDDeviceClose, IOHIDDeviceCopyMatchingElements, IOHIDDeviceCreate, IOHIDDeviceGetProperty, IOHIDDeviceGetReport, IOHIDDeviceGetTypeID, IOHIDDeviceOpen, IOHIDDeviceScheduleWithRunLoop, IOHIDDeviceSetReport, IOHIDDeviceUnscheduleFromRunLoop};
+use io_kit_sys::hid::element::{IOHIDElementGetCollectionType, IOHIDElementGetParent, IOHIDElementGetUsage, IOHIDElementGetUsagePage};
+use io_kit_sys::hid::keys::{
+    kIOHIDElementCollectionTypeApplication, kIOHIDElementCollectionTypeKey, kIOHIDElementTypeCollection, kIOHIDElementTypeKey, kIOHIDMaxInputReportSizeKey,
+    IOHIDReportType
+};
+use io_kit_sys::ret::{kIOReturnExclusiveAccess, kIOReturnNotPermitted, kIOReturnSuccess, IOReturn};
 use io_kit_sys::types::IOOptionBits;
 
 use crate::backend::iohidmanager::service::{IOService, RegistryEntryId};
@@ -21,6 +27,7 @@ use crate::{ensure, HidError, HidResult};
 extern "C" {
     // Workaround for https://github.com/jtakakura/io-kit-rs/issues/6
     fn IOHIDDeviceRegisterInputReportCallback(device: IOHIDDeviceRef, report: *mut u8, report_length: CFIndex, callback: Option<IOHIDReportCallback>, context: *mut c_void);
+    fn IOHIDDeviceRegisterRemovalCallback(device: IOHIDDeviceRef, callback: Option<IOHIDCallback>, context: *mut c_void);
 }
 
 #[derive(Debug)]
@@ -71,21 +78,44 @@ impl IOHIDDevice {
     pub fn property<T: ConcreteCFType>(&self, key: impl Key) -> HidResult<T> {
         self.untyped_property(key)?
             .downcast_into::<T>()
-            .ok_or(HidError::custom("Failed to cast property"))
+            .ok_or_else(|| HidError::custom("Failed to cast property"))
     }
 
     pub fn get_i32_property(&self, key: impl Key) -> HidResult<i32> {
         self.property::<CFNumber>(key)
-            .and_then(|v| v.to_i32().ok_or(HidError::custom("Property is not an i32")))
+            .and_then(|v| v.to_i32().ok_or_else(|| HidError::custom("Property is not an i32")))
     }
 
     pub fn get_string_property(&self, key: impl Key) -> HidResult<String> {
         self.property::<CFString>(key).map(|v| v.to_string())
     }
 
+    /// The usage pages and usage ids of the top-level application collections in the element tree of the device
+    pub fn application_collections(&self) -> HidResult<Vec<(u16, u16)>> {
+        let matching = CFDictionary::from_CFType_pairs(&[
+            (kIOHIDElementTypeKey.to_string(), CFNumber::from(kIOHIDElementTypeCollection as i32)),
+            (kIOHIDElementCollectionTypeKey.to_string(), CFNumber::from(kIOHIDElementCollectionTypeApplication as i32))
+        ]);
+        let elements = unsafe { IOHIDDeviceCopyMatchingElements(self.as_concrete_TypeRef(), matching.as_concrete_TypeRef(), 0) };
+        ensure!(!elements.is_null(), HidError::custom("Failed to copy the collection elements"));
+        let elements = unsafe { CFArray::<*const c_void>::wrap_under_create_rule(elements) };
+        let collections = elements
+            .iter()
+            .map(|element| *element as IOHIDElementRef)
+            .filter(|element| is_top_level(*element))
+            .map(|element| unsafe { (IOHIDElementGetUsagePage(element) as u16, IOHIDElementGetUsage(element) as u16) })
+            .collect();
+        Ok(collections)
+    }
+
     pub fn open(&self, options: IOOptionBits) -> HidResult<()> {
         let ret = unsafe { IOHIDDeviceOpen(self.as_concrete_TypeRef(), options) };
-        //TODO check for kIOReturnNotPermitted
+        ensure!(ret != kIOReturnExclusiveAccess, HidError::busy());
+        // Keyboards and other devices that can capture user input are protected by the privacy settings (TCC)
+        ensure!(
+            ret != kIOReturnNotPermitted,
+            HidError::permission_denied("Input Monitoring access is required, grant it under System Settings > Privacy & Security > Input Monitoring")
+        );
         ensure!(
             ret == kIOReturnSuccess,
             HidError::custom(format!("failed to open IOHIDDevice: {:?}", ret))
@@ -120,6 +150,13 @@ impl IOHIDDevice {
         Ok(())
     }
 
+    pub fn get_report(&self, report_type: IOHIDReportType, report_id: CFIndex, report: &mut [u8]) -> HidResult<usize> {
+        let mut length = report.len() as CFIndex;
+        let ret = unsafe { IOHIDDeviceGetReport(self.as_concrete_TypeRef(), report_type, report_id, report.as_mut_ptr(), &mut length) };
+        ensure!(ret == kIOReturnSuccess, HidError::custom(format!("Failed to get report: {}", ret)));
+        Ok(length as usize)
+    }
+
     pub fn register_input_report_callback<F>(&self, callback: F) -> HidResult<CallbackGuard>
         where
             F: FnMut(&[u8]) + Send + Sync + 'static
@@ -153,6 +190,38 @@ impl IOHIDDevice {
             callback_ptr,
         })
     }
+
+    /// Registers a callback that is called once the device got removed, while the device is scheduled with a run loop
+    pub fn register_removal_callback<F>(&self, callback: F) -> RemovalCallbackGuard
+        where
+            F: FnMut() + Send + Sync + 'static
+    {
+        let callback: RemovalCallback = Box::new(callback);
+        let callback: RemovalCallbackContainer = Box::new(callback);
+
+        let callback_ptr = Box::into_raw(callback);
+
+        unsafe {
+            IOHIDDeviceRegisterRemovalCallback(self.as_concrete_TypeRef(), Some(hid_removal_callback), callback_ptr as _);
+        }
+
+        RemovalCallbackGuard {
+            device: self.clone(),
+            callback_ptr,
+        }
+    }
+}
+
+/// Application collections may be nested, only the outermost one corresponds to a top-level collection
+fn is_top_level(element: IOHIDElementRef) -> bool {
+    let mut parent = unsafe { IOHIDElementGetParent(element) };
+    while !parent.is_null() {
+        if unsafe { IOHIDElementGetCollectionType(parent) } == kIOHIDElementCollectionTypeApplication {
+            return false;
+        }
+        parent = unsafe { IOHIDElementGetParent(parent) };
+    }
+    true
 }
 
 type InputReportCallback = Box<dyn FnMut(&[u8]) + Send + Sync>;
@@ -182,6 +251,33 @@ impl Drop for CallbackGuard {
     }
 }
 
+type RemovalCallback = Box<dyn FnMut() + Send + Sync>;
+type RemovalCallbackContainer = Box<RemovalCallback>;
+
+#[must_use = "The callback will be unregistered when the returned guard is dropped"]
+pub struct RemovalCallbackGuard {
+    device: IOHIDDevice,
+    callback_ptr: *mut RemovalCallback,
+}
+
+unsafe impl Send for RemovalCallbackGuard {}
+unsafe impl Sync for RemovalCallbackGuard {}
+
+impl Drop for RemovalCallbackGuard {
+    fn drop(&mut self) {
+        unsafe {
+            IOHIDDeviceRegisterRemovalCallback(self.device.as_concrete_TypeRef(), None, null_mut())
+        }
+
+        drop(unsafe { RemovalCallbackContainer::from_raw(self.callback_ptr) });
+    }
+}
+
+unsafe extern "C" fn hid_removal_callback(context: *mut c_void, _result: IOReturn, _sender: *mut c_void) {
+    let callback: &mut RemovalCallback = &mut *(context as *mut RemovalCallback);
+    callback();
+}
+
 unsafe extern "C" fn hid_report_callback(
     context: *mut c_void, _result: IOReturn, _sender: *mut c_void, _report_type: IOHIDReportType, _report_id: u32, report: *mut u8,
     report_length: CFIndex
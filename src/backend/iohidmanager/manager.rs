@@ -28,7 +28,11 @@ impl IOHIDManager {
 
     pub fn get_devices(&mut self) -> HidResult<Vec<IOHIDDevice>> {
         let devices = unsafe { IOHIDManagerCopyDevices(self.0) };
-        ensure!(!devices.is_null(), HidError::custom("Failed to copy device list"));
+        if devices.is_null() {
+            // IOHIDManagerCopyDevices also returns null if no devices are present or none of them are accessible
+            log::trace!("IOHIDManager did not return a device list, assuming that no devices are available");
+            return Ok(Vec::new());
+        }
         let devices: CFSet<IOHIDDeviceRef> = unsafe { CFSet::wrap_under_create_rule(devices) };
 
         let num_devices = devices.len();
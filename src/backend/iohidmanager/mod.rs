@@ -3,50 +3,100 @@ mod manager;
 mod runloop;
 mod service;
 mod utils;
+#[cfg(test)]
+mod tests;
 
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_channel::{bounded, Receiver, TrySendError};
 use bytes::{BufMut, Bytes, BytesMut};
-use core_foundation::array::CFArray;
 use core_foundation::base::TCFType;
-use core_foundation::dictionary::CFDictionary;
+use core_foundation::data::CFData;
 use core_foundation::runloop::{kCFRunLoopDefaultMode, CFRunLoop};
 use core_foundation::string::CFString;
-use futures_core::Stream;
+use futures_lite::stream::iter;
+use futures_lite::{Stream, StreamExt};
 use io_kit_sys::hid::keys::*;
 use io_kit_sys::types::IOOptionBits;
 
-use crate::backend::iohidmanager::device::{CallbackGuard, IOHIDDevice};
+use crate::backend::iohidmanager::device::{CallbackGuard, IOHIDDevice, RemovalCallbackGuard};
 use crate::backend::iohidmanager::manager::IOHIDManager;
 use crate::backend::iohidmanager::runloop::RunLoop;
 use crate::backend::iohidmanager::service::{IOService, RegistryEntryId};
-use crate::backend::iohidmanager::utils::{iter, CFDictionaryExt};
-use crate::{ensure, AccessMode, DeviceInfo, ErrorSource, HidError, HidResult, SerialNumberExt};
+use crate::timer::{poll_once, sleep_until};
+use crate::{ensure, AccessMode, BusType, Device, DeviceInfo, DISCONNECT_POLL_INTERVAL, ErrorSource, HidError, HidResult, MacDeviceExt, ManufacturerExt, ReportIds, RetryPolicy, SerialNumberExt};
 
 pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Send + Unpin> {
+    enumerate_with_retry(RetryPolicy::NONE).await
+}
+
+pub async fn enumerate_passive() -> HidResult<impl Stream<Item = DeviceInfo> + Send + Unpin> {
+    // All properties are cached in the I/O Registry, so enumerating never talks to the device
+    enumerate().await
+}
+
+pub async fn count() -> HidResult<usize> {
+    Ok(IOHIDManager::new()?.get_devices()?.len())
+}
+
+pub async fn enumerate_with_retry(policy: RetryPolicy) -> HidResult<impl Stream<Item = DeviceInfo> + Send + Unpin> {
     let mut manager = IOHIDManager::new()?;
-    let devices = manager
-        .get_devices()?
-        .into_iter()
-        .map(get_device_infos)
-        .filter_map(|r| {
-            r.map_err(|e| log::trace!("Failed to query device information\n\tbecause {e:?}"))
-                .ok()
+    let devices = iter(manager.get_devices()?)
+        .then(move |device| {
+            Box::pin(async move {
+                policy
+                    .run(|| get_device_infos(&device))
+                    .await
+                    .map_err(|e| log::trace!("Failed to query device information\n\tbecause {e:?}"))
+                    .unwrap_or_default()
+            })
         })
-        .flatten();
+        .flat_map(iter);
 
-    Ok(iter(devices))
+    Ok(devices)
 }
 
-fn get_device_infos(device: IOHIDDevice) -> HidResult<Vec<DeviceInfo>> {
-    let primary_usage_page = device.get_i32_property(kIOHIDPrimaryUsagePageKey)? as u16;
-    let primary_usage = device.get_i32_property(kIOHIDPrimaryUsageKey)? as u16;
+/// The usb interface class of hid devices
+const USB_CLASS_HID: i32 = 0x03;
+
+fn get_device_infos(device: &IOHIDDevice) -> HidResult<Vec<DeviceInfo>> {
     let vendor_id = device.get_i32_property(kIOHIDVendorIDKey)? as u16;
     let product_id = device.get_i32_property(kIOHIDProductIDKey)? as u16;
-    let serial_number = device.get_string_property(kIOHIDProductKey).ok();
+    let serial_number = device.get_string_property(kIOHIDSerialNumberKey).ok();
+    let manufacturer = device.get_string_property(kIOHIDManufacturerKey).ok();
+    let location_id = device.get_i32_property(kIOHIDLocationIDKey).ok().map(|id| id as u32);
     let name = device.get_string_property(kIOHIDProductKey)?;
-    let id = IOService::try_from(&device).and_then(|i| i.get_registry_entry_id())?;
+    let service = IOService::try_from(device)?;
+    let id = service.get_registry_entry_id()?;
+    let transport = device.get_string_property(kIOHIDTransportKey).ok();
+    // The interface attributes belong to the parent usb interface. As the search walks up the whole registry,
+    // it is limited to usb devices and hid interfaces, otherwise it may find the interface of a usb bluetooth radio for example
+    let interface_class = match transport.as_deref() {
+        Some("USB") if service.search_i32_property("bInterfaceClass") == Some(USB_CLASS_HID) => service
+            .search_i32_property("bInterfaceSubClass")
+            .zip(service.search_i32_property("bInterfaceProtocol"))
+            .map(|(subclass, protocol)| (subclass as u8, protocol as u8)),
+        _ => None
+    };
+
+    // Vendor devices may lack the usage properties, they are kept with usage page and usage id 0
+    let (primary_usage_page, primary_usage) = device
+        .get_i32_property(kIOHIDPrimaryUsagePageKey)
+        .and_then(|page| Ok((page as u16, device.get_i32_property(kIOHIDPrimaryUsageKey)? as u16)))
+        .unwrap_or_else(|e| {
+            log::trace!("Defaulting the usage of {name:?} to 0\n\tbecause {e:?}");
+            (0, 0)
+        });
+    let mut collections = device
+        .application_collections()
+        .map_err(|e| log::trace!("Failed to query the top-level collections of {name:?}\n\tbecause {e:?}"))
+        .unwrap_or_default();
+    if !collections.contains(&(primary_usage_page, primary_usage)) {
+        collections.insert(0, (primary_usage_page, primary_usage));
+    }
+    let collections: Arc<[(u16, u16)]> = collections.into();
 
     let info = DeviceInfo {
         id: id.into(),
@@ -55,24 +105,22 @@ fn get_device_infos(device: IOHIDDevice) -> HidResult<Vec<DeviceInfo>> {
         vendor_id,
         usage_id: primary_usage,
         usage_page: primary_usage_page,
+        collections: collections.clone(),
         private_data: BackendPrivateData {
-            serial_number
+            serial_number,
+            manufacturer,
+            location_id,
+            interface_class,
+            bus: transport.as_deref().map_or(BusType::Unknown, bus_from_transport)
         }
     };
 
     let mut results = Vec::new();
     results.extend(
-        device
-            .property::<CFArray>(kIOHIDDeviceUsagePairsKey)?
+        collections
             .iter()
-            .map(|i| unsafe { CFDictionary::wrap_under_get_rule(*i as _) })
-            .filter_map(|dict| {
-                let usage = dict.lookup_i32(kIOHIDDeviceUsageKey).ok()? as u16;
-                let usage_page = dict.lookup_i32(kIOHIDDeviceUsagePageKey).ok()? as u16;
-                Some((usage, usage_page))
-            })
-            .filter(|(usage, usage_page)| (*usage_page != primary_usage_page) || (*usage != primary_usage))
-            .map(|(usage_id, usage_page)| DeviceInfo {
+            .filter(|&&usage| usage != (primary_usage_page, primary_usage))
+            .map(|&(usage_page, usage_id)| DeviceInfo {
                 usage_id,
                 usage_page,
                 ..info.clone()
@@ -86,7 +134,10 @@ fn get_device_infos(device: IOHIDDevice) -> HidResult<Vec<DeviceInfo>> {
 struct InputReceiver {
     run_loop: Arc<RunLoop>,
     _callback: CallbackGuard,
-    read_channel: Receiver<Bytes>
+    _removal_callback: RemovalCallbackGuard,
+    read_channel: Receiver<Bytes>,
+    removed: Receiver<()>,
+    dropped: Arc<AtomicU64>
 }
 
 impl InputReceiver {
@@ -95,22 +146,33 @@ impl InputReceiver {
         let (sender, receiver) = bounded(64);
 
         let drain = receiver.clone();
+        let dropped = Arc::new(AtomicU64::new(0));
+        let counter = dropped.clone();
         let callback = device.register_input_report_callback(move |report| {
             byte_buffer.put(report);
             let mut bytes = byte_buffer.split().freeze();
             while let Err(TrySendError::Full(ret)) = sender.try_send(bytes) {
                 log::trace!("Dropping previous input report because the queue is full");
-                let _ = drain.try_recv();
+                if drain.try_recv().is_ok() {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
                 bytes = ret;
             }
         })?;
+        // Nothing is ever sent, dropping the sender closes the channel and wakes all receivers
+        let (removal_sender, removed) = bounded(1);
+        let mut removal_sender = Some(removal_sender);
+        let removal_callback = device.register_removal_callback(move || drop(removal_sender.take()));
         let run_loop = RunLoop::get_run_loop().await?;
         run_loop.schedule_device(&device)?;
 
         Ok(Self {
             run_loop,
             _callback: callback,
-            read_channel: receiver
+            _removal_callback: removal_callback,
+            read_channel: receiver,
+            removed,
+            dropped
         })
     }
 
@@ -131,6 +193,7 @@ impl InputReceiver {
 }
 
 pub struct BackendDevice {
+    id: RegistryEntryId,
     device: IOHIDDevice,
     open_options: IOOptionBits,
     input_receiver: Option<InputReceiver>
@@ -159,12 +222,29 @@ pub async fn open(id: &BackendDeviceId, mode: AccessMode) -> HidResult<BackendDe
     };
 
     Ok(BackendDevice {
+        id: *id,
         device,
         open_options,
         input_receiver
     })
 }
 
+pub fn string_descriptor(_id: &BackendDeviceId, _index: u8) -> HidResult<Option<String>> {
+    // IOHIDDevice only exposes the standard strings as properties
+    Err(HidError::unsupported())
+}
+
+pub fn is_claimed(id: &BackendDeviceId) -> bool {
+    // Opening fails with kIOReturnExclusiveAccess if another client seized the device
+    IOHIDDevice::try_from(*id)
+        .and_then(|device| {
+            device.open(0)?;
+            device.close(0)
+        })
+        .map_err(|e| log::trace!("Failed to open device\n\tbecause {e:?}"))
+        .is_err()
+}
+
 impl BackendDevice {
     pub async fn read_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
         ensure!(!buf.is_empty(), HidError::zero_sized_data());
@@ -179,21 +259,116 @@ impl BackendDevice {
         Ok(length)
     }
 
+    pub fn try_read_input_report(&self, buf: &mut [u8]) -> HidResult<Option<usize>> {
+        poll_once(self.read_input_report(buf)).transpose()
+    }
+
+    pub async fn disconnected(&self) -> HidResult<()> {
+        match &self.input_receiver {
+            Some(input) => {
+                // Fails once the removal callback closed the channel
+                let _ = input.removed.recv().await;
+            }
+            // Write-only devices aren't scheduled on a run loop, so no callbacks are delivered for them.
+            // The registry entry of the device is removed together with the device
+            None => {
+                while IOHIDDevice::try_from(self.id).is_ok() {
+                    sleep_until(Instant::now() + DISCONNECT_POLL_INTERVAL).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn dropped_reports(&self) -> u64 {
+        self.input_receiver
+            .as_ref()
+            .map_or(0, |input| input.dropped.load(Ordering::Relaxed))
+    }
+
     pub async fn write_output_report(&self, buf: &[u8]) -> HidResult<()> {
         ensure!(!buf.is_empty(), HidError::zero_sized_data());
 
         let report_id = buf[0];
         let data_to_send = if report_id == 0x0 { &buf[1..] } else { buf };
 
-        self.device.set_report(1, report_id as _, data_to_send)
+        self.device.set_report(kIOHIDReportTypeOutput, report_id as _, data_to_send)
+    }
+
+    pub async fn set_output_report(&self, buf: &[u8]) -> HidResult<()> {
+        // IOHIDDeviceSetReport already uses the control pipe if the device has no interrupt out endpoint
+        self.write_output_report(buf).await
+    }
+
+    pub async fn flush(&self) -> HidResult<()> {
+        // IOHIDDeviceSetReport is synchronous
+        Ok(())
+    }
+
+    pub async fn send_feature_report(&self, buf: &[u8]) -> HidResult<()> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+
+        let report_id = buf[0];
+        let data_to_send = if report_id == 0x0 { &buf[1..] } else { buf };
+
+        self.device.set_report(kIOHIDReportTypeFeature, report_id as _, data_to_send)
+    }
+
+    pub async fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+
+        let report_id = buf[0];
+        // Unnumbered reports are returned without the leading report id
+        match report_id {
+            0x0 => Ok(self.device.get_report(kIOHIDReportTypeFeature, 0, &mut buf[1..])? + 1),
+            _ => self.device.get_report(kIOHIDReportTypeFeature, report_id as _, buf)
+        }
+    }
+
+    pub async fn get_output_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+
+        let report_id = buf[0];
+        // Unnumbered reports are returned without the leading report id
+        match report_id {
+            0x0 => Ok(self.device.get_report(kIOHIDReportTypeOutput, 0, &mut buf[1..])? + 1),
+            _ => self.device.get_report(kIOHIDReportTypeOutput, report_id as _, buf)
+        }
+    }
+
+    pub async fn physical_descriptor(&self) -> HidResult<Option<Vec<u8>>> {
+        Err(HidError::unsupported())
+    }
+
+    pub async fn report_ids(&self) -> HidResult<ReportIds> {
+        let descriptor = self.device.property::<CFData>(kIOHIDReportDescriptorKey)?;
+        Ok(ReportIds::from_descriptor(descriptor.bytes()))
+    }
+
+    pub async fn report_descriptor(&self) -> HidResult<Vec<u8>> {
+        let descriptor = self.device.property::<CFData>(kIOHIDReportDescriptorKey)?;
+        Ok(descriptor.bytes().to_vec())
+    }
+}
+
+impl MacDeviceExt for Device {
+    fn io_hid_device(&self) -> io_kit_sys::hid::base::IOHIDDeviceRef {
+        self.inner.device.as_concrete_TypeRef()
     }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct BackendPrivateData {
     serial_number: Option<String>,
+    manufacturer: Option<String>,
+    location_id: Option<u32>,
+    interface_class: Option<(u8, u8)>,
+    bus: BusType
 }
 
+/// IOHIDDeviceSetReport is synchronous, so a dropped write never reached the device
+pub const DROPPED_WRITES_CONTINUE: bool = false;
+
 pub type BackendDeviceId = RegistryEntryId;
 pub type BackendError = ();
 
@@ -203,6 +378,58 @@ impl From<BackendError> for ErrorSource {
     }
 }
 
+pub fn is_disconnected(_error: &BackendError) -> bool {
+    // IOKit errors are not preserved, so disconnects can't be told apart from other failures
+    false
+}
+
+impl DeviceInfo {
+    /// The USB location id, which encodes the port the device is connected to
+    pub(crate) fn physical_location(&self) -> Option<String> {
+        self.private_data
+            .location_id
+            .map(|id| format!("{id:08x}"))
+    }
+
+    /// The bus number followed by the port numbers that make up the location id, i.e `14/3/2` for `0x14320000`
+    pub(crate) fn topology(&self) -> Option<String> {
+        let id = self.private_data.location_id?;
+        // Every hub level uses one nibble below the bus number, the first zero nibble ends the chain
+        let ports = (0..6)
+            .map(|level| (id >> (20 - 4 * level)) & 0xf)
+            .take_while(|port| *port != 0)
+            .map(|port| format!("/{port}"));
+        Some(format!("{:02x}", id >> 24) + &ports.collect::<String>())
+    }
+
+    /// Found by searching the parents of the device in the service plane
+    pub(crate) fn interface_class(&self) -> Option<(u8, u8)> {
+        self.private_data.interface_class
+    }
+
+    pub(crate) fn connected_bus(&self) -> BusType {
+        self.private_data.bus
+    }
+}
+
+/// Maps the value of `kIOHIDTransportKey`
+fn bus_from_transport(transport: &str) -> BusType {
+    match transport {
+        "USB" => BusType::Usb,
+        "Bluetooth" | "Bluetooth Low Energy" | "BluetoothLowEnergy" => BusType::Bluetooth,
+        "I2C" => BusType::I2c,
+        "SPI" => BusType::Spi,
+        "Virtual" => BusType::Virtual,
+        _ => BusType::Unknown
+    }
+}
+
+impl ManufacturerExt for DeviceInfo {
+    fn manufacturer(&self) -> Option<&str> {
+        self.private_data.manufacturer.as_deref()
+    }
+}
+
 impl SerialNumberExt for DeviceInfo {
     fn serial_number(&self) -> Option<&str> {
         self.private_data
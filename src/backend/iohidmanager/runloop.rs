@@ -72,7 +72,9 @@ impl RunLoop {
     async fn new() -> HidResult<Self> {
         let (sender, receiver) = bounded(1);
 
-        let thread = Some(thread::spawn(|| {
+        // The run loop is shared by all devices, so the thread is named after the library instead of a single device
+        let builder = thread::Builder::new().name("async-hid-runloop".into());
+        let thread = Some(builder.spawn(|| {
             log::trace!("Creating new run loop");
 
             let run_loop_mode = CFString::new(&format!("ASYNC_HID_{:?}", thread::current().id()));
@@ -131,7 +133,7 @@ impl RunLoop {
             }
 
             log::trace!("Stopping run loop");
-        }));
+        }).map_err(|e| HidError::custom(format!("Failed to spawn the run loop thread: {e}")))?);
 
         let sender = receiver
             .recv()
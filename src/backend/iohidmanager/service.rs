@@ -1,10 +1,14 @@
-use core_foundation::base::TCFType;
+use core_foundation::base::{kCFAllocatorDefault, CFType, TCFType};
 use core_foundation::dictionary::CFMutableDictionaryRef;
+use core_foundation::number::CFNumber;
+use core_foundation::string::CFString;
 use io_kit_sys::hid::device::IOHIDDeviceGetService;
+use io_kit_sys::keys::kIOServicePlane;
 use io_kit_sys::ret::kIOReturnSuccess;
 use io_kit_sys::types::io_service_t;
 use io_kit_sys::{
-    kIOMasterPortDefault, IOObjectRelease, IOObjectRetain, IORegistryEntryGetRegistryEntryID, IORegistryEntryIDMatching, IOServiceGetMatchingService
+    kIOMasterPortDefault, kIORegistryIterateParents, kIORegistryIterateRecursively, IOObjectRelease, IOObjectRetain, IORegistryEntryGetRegistryEntryID,
+    IORegistryEntryIDMatching, IORegistryEntrySearchCFProperty, IOServiceGetMatchingService
 };
 use mach2::port::MACH_PORT_NULL;
 
@@ -53,6 +57,26 @@ impl IOService {
         ensure!(result == kIOReturnSuccess, HidError::custom("Failed to retrieve entry id"));
         Ok(RegistryEntryId(entry_id))
     }
+
+    /// Searches this service and all its parents in the service plane for a numeric property
+    pub fn search_i32_property(&self, key: &str) -> Option<i32> {
+        let key = CFString::new(key);
+        let value = unsafe {
+            IORegistryEntrySearchCFProperty(
+                self.0,
+                kIOServicePlane,
+                key.as_concrete_TypeRef(),
+                kCFAllocatorDefault,
+                kIORegistryIterateRecursively | kIORegistryIterateParents
+            )
+        };
+        if value.is_null() {
+            return None;
+        }
+        unsafe { CFType::wrap_under_create_rule(value) }
+            .downcast_into::<CFNumber>()?
+            .to_i32()
+    }
 }
 
 impl Drop for IOService {
@@ -61,7 +85,7 @@ impl Drop for IOService {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
 pub struct RegistryEntryId(u64);
 
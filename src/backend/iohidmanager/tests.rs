@@ -0,0 +1,30 @@
+//! Tests of the IOHIDManager backend
+//!
+//! The tests against real devices require the described hardware to be connected and are therefore ignored by default.
+
+use futures_lite::future::block_on;
+use futures_lite::StreamExt;
+
+use crate::DeviceInfo;
+
+const KEYBOARD: (u16, u16) = (0x01, 0x06);
+const SYSTEM_CONTROL: (u16, u16) = (0x01, 0x80);
+
+#[test]
+#[ignore = "requires a keyboard with a system control collection"]
+fn keyboard_with_system_control() {
+    let devices: Vec<DeviceInfo> = block_on(async { DeviceInfo::enumerate().await.unwrap().collect().await });
+    let keyboard = devices
+        .iter()
+        .find(|info| info.collections().contains(&KEYBOARD) && info.collections().contains(&SYSTEM_CONTROL))
+        .expect("No keyboard with a system control collection is connected");
+
+    // Every top-level collection is exposed as a separate DeviceInfo of the same device
+    let usages: Vec<(u16, u16)> = devices
+        .iter()
+        .filter(|info| info.id == keyboard.id)
+        .map(|info| (info.usage_page, info.usage_id))
+        .collect();
+    assert_eq!(usages.len(), keyboard.collection_count());
+    assert!(usages.contains(&KEYBOARD) && usages.contains(&SYSTEM_CONTROL), "{usages:?}");
+}
@@ -1,24 +1,41 @@
 #[cfg(all(target_os = "windows", feature = "win32"))]
 mod win32;
 #[cfg(all(target_os = "windows", feature = "win32"))]
-pub use win32::{enumerate, open, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData};
+pub use win32::{count, enumerate, enumerate_passive, enumerate_with_retry, is_claimed, is_disconnected, open, string_descriptor, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData, DROPPED_WRITES_CONTINUE};
 
 #[cfg(all(target_os = "windows", feature = "winrt"))]
 mod winrt;
 #[cfg(all(target_os = "windows", feature = "winrt"))]
-pub use winrt::{enumerate, open, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData};
+pub use winrt::{count, enumerate, enumerate_passive, enumerate_with_retry, is_claimed, is_disconnected, open, string_descriptor, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData, DROPPED_WRITES_CONTINUE};
 
 #[cfg(all(feature = "win32", feature = "winrt"))]
 compile_error!("Only win32 or winrt can be active at the same time");
 
+/// The name of the backend that was selected at compile time
+pub const fn name() -> &'static str {
+    if cfg!(all(target_os = "windows", feature = "win32")) {
+        "win32"
+    } else if cfg!(all(target_os = "windows", feature = "winrt")) {
+        "winrt"
+    } else if cfg!(target_os = "linux") {
+        "hidraw"
+    } else if cfg!(target_os = "macos") {
+        "iohidmanager"
+    } else {
+        "unknown"
+    }
+}
+
 
 #[cfg(target_os = "linux")]
 mod hidraw;
 #[cfg(target_os = "linux")]
-pub use hidraw::{enumerate, open, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData};
+pub use hidraw::{count, enumerate, enumerate_passive, enumerate_with_retry, is_claimed, is_disconnected, open, string_descriptor, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData, DROPPED_WRITES_CONTINUE};
+#[cfg(target_os = "linux")]
+pub use hidraw::{VirtualDevice, VirtualDeviceInfo};
 
 
 #[cfg(target_os = "macos")]
 mod iohidmanager;
 #[cfg(target_os = "macos")]
-pub use iohidmanager::{enumerate, open, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData};
+pub use iohidmanager::{count, enumerate, enumerate_passive, enumerate_with_retry, is_claimed, is_disconnected, open, string_descriptor, BackendDevice, BackendDeviceId, BackendError, BackendPrivateData, DROPPED_WRITES_CONTINUE};
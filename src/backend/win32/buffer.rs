@@ -10,7 +10,7 @@ use windows::Win32::System::IO::{CancelIoEx, GetOverlappedResult, OVERLAPPED};
 use windows::Win32::System::Threading::CreateEventW;
 use crate::backend::win32::device::Device;
 use crate::backend::win32::waiter::WaitableHandleFuture;
-use crate::HidResult;
+use crate::{ensure, HidError, HidResult};
 
 #[derive(Debug)]
 pub struct Readable;
@@ -123,7 +123,8 @@ impl IoBuffer<Readable> {
         })
     }
 
-    pub async fn read(&mut self, buf: &mut[u8]) -> HidResult<usize> {
+    /// Reads the next input report, removing the report id if `strip_zero_id` is set and the report id is 0
+    pub async fn read(&mut self, buf: &mut[u8], strip_zero_id: bool) -> HidResult<usize> {
         loop {
             match self.pending {
                 false => self.start_read()?,
@@ -131,7 +132,7 @@ impl IoBuffer<Readable> {
                     Some(size) => {
                         trace!("Completed read operation (retrieved {} bytes)", size);
                         let mut data = &self.buffer[..size];
-                        if data[0] == 0x0 {
+                        if strip_zero_id && data[0] == 0x0 {
                             data = &data[1..];
                         }
                         let mut copy_len = data.len();
@@ -152,7 +153,7 @@ impl IoBuffer<Readable> {
 
 impl IoBuffer<Writable> {
 
-     async fn wait_for_write_to_complete(&mut self) -> HidResult<()> {
+    pub async fn wait_for_write_to_complete(&mut self) -> HidResult<()> {
         if self.pending {
             loop {
                 match self.get_result()? {
@@ -184,11 +185,12 @@ impl IoBuffer<Writable> {
         self.wait_for_write_to_complete().await.unwrap_or_else(|err| error!("Abandoned write failed: {err}"));
 
         trace!("Filling write buffer with data");
-        let mut data_size = data.len();
-        if data_size > self.buffer.len() {
-            debug!("Data size ({}) exceeds maximum buffer size ({}), truncating data", data_size, self.buffer.len());
-            data_size = self.buffer.len();
-        }
+        // A HID report can't be split into multiple writes, so reports longer than the declared output report length can't be sent at all
+        let data_size = data.len();
+        ensure!(
+            data_size <= self.buffer.len(),
+            HidError::custom(format!("Output report of {} bytes exceeds the output report length of {} bytes", data_size, self.buffer.len()))
+        );
         self.buffer[data_size..].fill(0);
         self.buffer[..data_size].copy_from_slice(&data[..data_size]);
 
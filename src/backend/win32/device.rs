@@ -1,6 +1,6 @@
 use std::ffi::c_void;
 use windows::core::PCWSTR;
-use windows::Win32::Devices::HumanInterfaceDevice::{HidD_FreePreparsedData, HidD_GetAttributes, HidD_GetPreparsedData, HidD_GetProductString, HidD_GetSerialNumberString, HidP_GetCaps, HIDD_ATTRIBUTES, HIDP_CAPS, PHIDP_PREPARSED_DATA};
+use windows::Win32::Devices::HumanInterfaceDevice::{HidD_FreePreparsedData, HidD_GetAttributes, HidD_GetIndexedString, HidD_GetManufacturerString, HidD_GetPreparsedData, HidD_GetProductString, HidD_GetSerialNumberString, HidP_GetButtonCaps, HidP_GetCaps, HidP_GetValueCaps, HIDD_ATTRIBUTES, HIDP_BUTTON_CAPS, HIDP_CAPS, HIDP_REPORT_TYPE, HIDP_VALUE_CAPS, PHIDP_PREPARSED_DATA};
 use windows::Win32::Foundation::{CloseHandle, BOOLEAN, HANDLE};
 use windows::Win32::Storage::FileSystem::{CreateFileW, FILE_FLAG_OVERLAPPED, FILE_SHARE_NONE, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING};
 use crate::{AccessMode, HidResult};
@@ -47,9 +47,9 @@ impl Device {
         PreparsedData::from_device(self)
     }
 
-    fn read_string(&self, func: unsafe fn(HANDLE, *mut c_void, u32) -> BOOLEAN) -> HidResult<String> {
+    fn read_string(&self, func: impl FnOnce(HANDLE, *mut c_void, u32) -> BOOLEAN) -> HidResult<String> {
         let mut buffer = [0u16; 256];
-        unsafe { func(self.0, buffer.as_mut_ptr() as _, (size_of::<u16>() * buffer.len()) as u32) }.ok()?;
+        func(self.0, buffer.as_mut_ptr() as _, (size_of::<u16>() * buffer.len()) as u32).ok()?;
         let serial_number = buffer
             .split(|c| *c == 0x0)
             .map(String::from_utf16_lossy)
@@ -59,11 +59,19 @@ impl Device {
     }
 
     pub fn serial_number(&self) -> HidResult<String> {
-        self.read_string(HidD_GetSerialNumberString)
+        self.read_string(|handle, buffer, len| unsafe { HidD_GetSerialNumberString(handle, buffer, len) })
     }
 
     pub fn name(&self) -> HidResult<String> {
-        self.read_string(HidD_GetProductString)
+        self.read_string(|handle, buffer, len| unsafe { HidD_GetProductString(handle, buffer, len) })
+    }
+
+    pub fn manufacturer(&self) -> HidResult<String> {
+        self.read_string(|handle, buffer, len| unsafe { HidD_GetManufacturerString(handle, buffer, len) })
+    }
+
+    pub fn indexed_string(&self, index: u8) -> HidResult<String> {
+        self.read_string(|handle, buffer, len| unsafe { HidD_GetIndexedString(handle, index as u32, buffer, len) })
     }
 
 }
@@ -96,6 +104,30 @@ impl PreparsedData {
         Ok(caps)
     }
 
+    pub fn button_caps(&self, report_type: HIDP_REPORT_TYPE, count: u16) -> HidResult<Vec<HIDP_BUTTON_CAPS>> {
+        let mut caps = vec![HIDP_BUTTON_CAPS::default(); count as usize];
+        let mut len = count;
+        if count > 0 {
+            unsafe {
+                HidP_GetButtonCaps(report_type, caps.as_mut_ptr(), &mut len, self.0).ok()?;
+            }
+        }
+        caps.truncate(len as usize);
+        Ok(caps)
+    }
+
+    pub fn value_caps(&self, report_type: HIDP_REPORT_TYPE, count: u16) -> HidResult<Vec<HIDP_VALUE_CAPS>> {
+        let mut caps = vec![HIDP_VALUE_CAPS::default(); count as usize];
+        let mut len = count;
+        if count > 0 {
+            unsafe {
+                HidP_GetValueCaps(report_type, caps.as_mut_ptr(), &mut len, self.0).ok()?;
+            }
+        }
+        caps.truncate(len as usize);
+        Ok(caps)
+    }
+
 }
 
 impl Drop for PreparsedData {
@@ -6,16 +6,19 @@ mod string;
 mod interface;
 mod mutex;
 
+use std::collections::BTreeSet;
 use std::sync::{Arc};
+use std::time::Instant;
 
-use futures_lite::Stream;
+use futures_lite::{Stream, StreamExt};
 use futures_lite::stream::iter;
 use windows::core::{HRESULT};
 use windows::Win32::Devices::DeviceAndDriverInstallation::{CM_MapCrToWin32Err, CONFIGRET};
-use windows::Win32::Devices::HumanInterfaceDevice::HidD_SetNumInputBuffers;
-use windows::Win32::Foundation::E_FAIL;
+use windows::Win32::Devices::HumanInterfaceDevice::{HidD_GetFeature, HidD_GetPhysicalDescriptor, HidD_SetFeature, HidD_SetNumInputBuffers, HidD_SetOutputReport, HidP_Feature, HidP_Input, HidP_Output};
+use windows::Win32::Foundation::{ERROR_BAD_COMMAND, ERROR_DEVICE_NOT_CONNECTED, E_FAIL};
 use crate::error::{ErrorSource, HidResult};
-use crate::{ensure, AccessMode, DeviceId, DeviceInfo, HidError, SerialNumberExt};
+use crate::timer::{poll_once, sleep_until};
+use crate::{ensure, AccessMode, BusType, DeviceId, DISCONNECT_POLL_INTERVAL, DeviceInfo, HidError, ManufacturerExt, ReportIds, RetryPolicy, SerialNumberExt, WindowsDeviceInfoExt};
 use crate::backend::win32::buffer::{IoBuffer, Readable, Writable};
 use crate::backend::win32::device::Device;
 use interface::Interface;
@@ -23,15 +26,76 @@ use crate::backend::win32::mutex::SimpleMutex;
 use crate::backend::win32::string::{U16Str, U16String};
 
 pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send> {
-    let devices = Interface::get_interface_list()?
+    enumerate_with_retry(RetryPolicy::NONE).await
+}
+
+pub async fn count() -> HidResult<usize> {
+    Ok(Interface::get_interface_list()?.iter().count())
+}
+
+pub async fn enumerate_with_retry(policy: RetryPolicy) -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send> {
+    enumerate_interfaces(policy, false)
+}
+
+pub async fn enumerate_passive() -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send> {
+    enumerate_interfaces(RetryPolicy::NONE, true)
+}
+
+fn enumerate_interfaces(policy: RetryPolicy, passive: bool) -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send> {
+    // The device information is queried lazily, so dropping the stream skips the remaining devices
+    let interfaces = Interface::get_interface_list()?
         .iter()
-        .filter_map(|i| {
-            get_device_information(i)
-                .map_err(|e| log::trace!("Failed to query device information for {i:?}\n\tbecause {e}"))
-                .ok()
-        })
+        .map(U16Str::to_owned)
         .collect::<Vec<_>>();
-    Ok(iter(devices))
+    let devices = iter(interfaces)
+        .then(move |i| {
+            Box::pin(async move {
+                policy
+                    .run(|| get_device_information(&i, passive))
+                    .await
+                    .map_err(|e| log::trace!("Failed to query device information for {i:?}\n\tbecause {e}"))
+                    .ok()
+            })
+        })
+        .filter_map(|info| info);
+    Ok(devices)
+}
+
+impl DeviceInfo {
+    /// The interface path, which contains the port based instance id for devices without a serial number
+    pub(crate) fn physical_location(&self) -> Option<String> {
+        Some(self.interface_path())
+    }
+
+    pub(crate) fn topology(&self) -> Option<String> {
+        // The interface path doesn't contain the parent devices, they are only available through the configuration manager
+        None
+    }
+
+    pub(crate) fn interface_class(&self) -> Option<(u8, u8)> {
+        // Like the topology, the usb interface is only available through the configuration manager
+        None
+    }
+
+    pub(crate) fn connected_bus(&self) -> BusType {
+        // The interface path only tells Bluetooth devices apart, the bus of other devices is only available through the configuration manager
+        match self.is_bluetooth() {
+            true => BusType::Bluetooth,
+            false => BusType::Unknown
+        }
+    }
+}
+
+impl WindowsDeviceInfoExt for DeviceInfo {
+    fn interface_path(&self) -> String {
+        self.id.0.to_string()
+    }
+}
+
+impl ManufacturerExt for DeviceInfo {
+    fn manufacturer(&self) -> Option<&str> {
+        self.private_data.manufacturer.as_deref()
+    }
 }
 
 impl SerialNumberExt for DeviceInfo {
@@ -43,22 +107,36 @@ impl SerialNumberExt for DeviceInfo {
     }
 }
 
-fn get_device_information(device: &U16Str) -> HidResult<DeviceInfo> {
+/// Retrieves the device information, skipping the optional strings that have to be requested from the device if `passive` is set
+fn get_device_information(device: &U16Str, passive: bool) -> HidResult<DeviceInfo> {
     let id = device.to_owned();
     let device = Device::open(device.as_ptr(), None)?;
     let name = device.name()?;
+    // The attributes and the preparsed data are cached by the HID class driver
     let attribs = device.attributes()?;
-    let caps = device.preparsed_data()?.caps()?;
-    let serial_number = device.serial_number().ok();
+    let (usage_page, usage_id) = device
+        .preparsed_data()
+        .and_then(|data| data.caps())
+        .map(|caps| (caps.UsagePage, caps.Usage))
+        .unwrap_or_else(|e| {
+            log::trace!("Defaulting the usage of {name:?} to 0\n\tbecause {e:?}");
+            (0, 0)
+        });
+    let (serial_number, manufacturer) = match passive {
+        true => (None, None),
+        false => (device.serial_number().ok(), device.manufacturer().ok().filter(|s| !s.is_empty()))
+    };
     Ok(DeviceInfo {
         id: DeviceId::from(id),
         name,
         product_id: attribs.ProductID,
         vendor_id: attribs.VendorID,
-        usage_id: caps.Usage,
-        usage_page: caps.UsagePage,
+        usage_id,
+        usage_page,
+        collections: Arc::new([(usage_page, usage_id)]),
         private_data: BackendPrivateData {
-            serial_number
+            serial_number,
+            manufacturer
         }
     })
 }
@@ -66,8 +144,12 @@ fn get_device_information(device: &U16Str) -> HidResult<DeviceInfo> {
 
 #[derive(Debug)]
 pub struct BackendDevice {
+    device: Arc<Device>,
     read_buffer: SimpleMutex<IoBuffer<Readable>>,
     write_buffer: SimpleMutex<IoBuffer<Writable>>,
+    output_report_length: usize,
+    feature_report_length: usize,
+    numbered_input: bool,
 }
 
 pub async fn open(id: &BackendDeviceId, mode: AccessMode) -> HidResult<BackendDevice> {
@@ -79,24 +161,71 @@ pub async fn open(id: &BackendDeviceId, mode: AccessMode) -> HidResult<BackendDe
     let caps = device.preparsed_data()?.caps()?;
 
     let read_buffer = SimpleMutex::new(IoBuffer::<Readable>::new(device.clone(), caps.InputReportByteLength as usize)?);
-    let write_buffer = SimpleMutex::new(IoBuffer::<Writable>::new(device, caps.OutputReportByteLength as usize)?);
-    Ok(BackendDevice {
+    let write_buffer = SimpleMutex::new(IoBuffer::<Writable>::new(device.clone(), caps.OutputReportByteLength as usize)?);
+    let mut device = BackendDevice {
+        device,
         read_buffer,
         write_buffer,
-    })
+        output_report_length: caps.OutputReportByteLength as usize,
+        feature_report_length: caps.FeatureReportByteLength as usize,
+        numbered_input: false,
+    };
+    // Windows prefixes unnumbered reports with id 0, but a device that mixes numbered and unnumbered reports delivers a real id 0
+    device.numbered_input = match device.report_ids().await {
+        Ok(report_ids) => !report_ids.input.is_empty(),
+        Err(e) => {
+            log::trace!("Failed to retrieve the report ids, stripping report id 0 from all input reports\n\tbecause {e:?}");
+            false
+        }
+    };
+    Ok(device)
+}
+
+pub fn string_descriptor(id: &BackendDeviceId, index: u8) -> HidResult<Option<String>> {
+    let device = Device::open(id.as_ptr(), None)?;
+    // Windows fails the request for indices that don't exist, which can't be told apart from other failures
+    let string = device
+        .indexed_string(index)
+        .map_err(|e| log::trace!("Failed to read string descriptor {index}\n\tbecause {e:?}"))
+        .ok();
+    Ok(string.filter(|s| !s.is_empty()))
+}
+
+pub fn is_claimed(id: &BackendDeviceId) -> bool {
+    // Keyboards, mice and devices that are opened exclusively by other processes refuse read/write access
+    Device::open(id.as_ptr(), Some(AccessMode::ReadWrite))
+        .map_err(|e| log::trace!("Failed to open device for read/write access\n\tbecause {e:?}"))
+        .is_err()
 }
 
 impl BackendDevice {
     pub async fn read_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
         match self.read_buffer.try_lock() {
             Some(mut buffer) => {
-                let len = buffer.read(buf).await?;
+                let len = buffer.read(buf, !self.numbered_input).await?;
                 Ok(len)
             },
             None => Err(HidError::custom("Another read operation is in progress"))
         }
     }
 
+    pub fn try_read_input_report(&self, buf: &mut [u8]) -> HidResult<Option<usize>> {
+        poll_once(self.read_input_report(buf)).transpose()
+    }
+
+    pub async fn disconnected(&self) -> HidResult<()> {
+        // The handle itself doesn't signal the removal, but requests on it fail with ERROR_DEVICE_NOT_CONNECTED afterwards
+        while self.device.attributes().is_ok() {
+            sleep_until(Instant::now() + DISCONNECT_POLL_INTERVAL).await;
+        }
+        Ok(())
+    }
+
+    pub fn dropped_reports(&self) -> u64 {
+        // The driver discards reports once its ring buffer is full without telling us
+        0
+    }
+
     pub async fn write_output_report(&self, buf: &[u8]) -> HidResult<()> {
         ensure!(!buf.is_empty(), HidError::zero_sized_data());
         match self.write_buffer.try_lock() {
@@ -107,13 +236,94 @@ impl BackendDevice {
             None => Err(HidError::custom("Another write operation is in progress"))
         }
     }
+
+    pub async fn set_output_report(&self, buf: &[u8]) -> HidResult<()> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        ensure!(buf.len() <= self.output_report_length, HidError::custom("Output report is too large"));
+        let mut buffer = vec![0u8; self.output_report_length];
+        buffer[..buf.len()].copy_from_slice(buf);
+        unsafe { HidD_SetOutputReport(self.device.handle(), buffer.as_ptr() as _, buffer.len() as u32) }.ok()?;
+        Ok(())
+    }
+
+    pub async fn flush(&self) -> HidResult<()> {
+        // Waits for writes whose futures got dropped before completing
+        match self.write_buffer.try_lock() {
+            Some(mut buffer) => buffer.wait_for_write_to_complete().await,
+            None => Err(HidError::custom("Another write operation is in progress"))
+        }
+    }
+
+    pub async fn send_feature_report(&self, buf: &[u8]) -> HidResult<()> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        ensure!(buf.len() <= self.feature_report_length, HidError::custom("Feature report is too large"));
+        let mut buffer = vec![0u8; self.feature_report_length];
+        buffer[..buf.len()].copy_from_slice(buf);
+        unsafe { HidD_SetFeature(self.device.handle(), buffer.as_ptr() as _, buffer.len() as u32) }.ok()?;
+        Ok(())
+    }
+
+    pub async fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        let mut buffer = vec![0u8; self.feature_report_length];
+        ensure!(!buffer.is_empty(), HidError::custom("Device has no feature reports"));
+        buffer[0] = buf[0];
+        unsafe { HidD_GetFeature(self.device.handle(), buffer.as_mut_ptr() as _, buffer.len() as u32) }.ok()?;
+        let size = buf.len().min(buffer.len());
+        buf[..size].copy_from_slice(&buffer[..size]);
+        Ok(size)
+    }
+
+    pub async fn get_output_report(&self, _buf: &mut [u8]) -> HidResult<usize> {
+        // Windows only allows reading input and feature reports
+        Err(HidError::unsupported())
+    }
+
+    pub async fn physical_descriptor(&self) -> HidResult<Option<Vec<u8>>> {
+        let mut buffer = vec![0u8; 4096];
+        if let Err(err) = unsafe { HidD_GetPhysicalDescriptor(self.device.handle(), buffer.as_mut_ptr() as _, buffer.len() as u32) }.ok() {
+            log::trace!("Failed to retrieve the physical descriptor: {err}");
+            return Ok(None);
+        }
+        // Descriptor set 0 contains the number of sets and the length of each set
+        let set_count = buffer[0] as usize;
+        let set_length = u16::from_le_bytes([buffer[1], buffer[2]]) as usize;
+        buffer.truncate((3 + set_count * set_length).min(buffer.len()));
+        Ok(Some(buffer))
+    }
+
+    pub async fn report_ids(&self) -> HidResult<ReportIds> {
+        let preparsed_data = self.device.preparsed_data()?;
+        let caps = preparsed_data.caps()?;
+        let report_ids_of = |report_type, button_caps, value_caps| -> HidResult<BTreeSet<u8>> {
+            let mut ids = BTreeSet::new();
+            ids.extend(preparsed_data.button_caps(report_type, button_caps)?.iter().map(|c| c.ReportID));
+            ids.extend(preparsed_data.value_caps(report_type, value_caps)?.iter().map(|c| c.ReportID));
+            ids.remove(&0);
+            Ok(ids)
+        };
+        Ok(ReportIds {
+            input: report_ids_of(HidP_Input, caps.NumberInputButtonCaps, caps.NumberInputValueCaps)?,
+            output: report_ids_of(HidP_Output, caps.NumberOutputButtonCaps, caps.NumberOutputValueCaps)?,
+            feature: report_ids_of(HidP_Feature, caps.NumberFeatureButtonCaps, caps.NumberFeatureValueCaps)?
+        })
+    }
+
+    pub async fn report_descriptor(&self) -> HidResult<Vec<u8>> {
+        // Windows only exposes the preparsed data, the original descriptor is not available
+        Err(HidError::unsupported())
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct BackendPrivateData {
-    serial_number: Option<String>
+    serial_number: Option<String>,
+    manufacturer: Option<String>
 }
 
+/// Overlapped writes keep running after their future got dropped
+pub const DROPPED_WRITES_CONTINUE: bool = true;
+
 pub type BackendDeviceId = U16String;
 pub type BackendError = windows::core::Error;
 
@@ -123,6 +333,12 @@ impl From<BackendError> for ErrorSource {
     }
 }
 
+pub fn is_disconnected(error: &BackendError) -> bool {
+    [ERROR_DEVICE_NOT_CONNECTED, ERROR_BAD_COMMAND]
+        .iter()
+        .any(|code| error.code() == HRESULT::from_win32(code.0))
+}
+
 impl From<CONFIGRET> for ErrorSource {
     fn from(value: CONFIGRET) -> Self {
         const UNKNOWN_ERROR: u32 = 0xFFFF;
@@ -5,21 +5,25 @@ use std::fmt::Display;
 use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
 use std::pin::Pin;
-use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::task::{Context, Poll};
 
 use flume::{Receiver, TrySendError};
 use futures_lite::{Stream, StreamExt};
-use windows::core::{h, HSTRING};
+use windows::core::{h, HRESULT, HSTRING};
 use windows::Devices::Enumeration::{DeviceInformation, DeviceInformationCollection};
 use windows::Devices::HumanInterfaceDevice::{HidDevice, HidInputReport, HidInputReportReceivedEventArgs};
 use windows::Foundation::{EventRegistrationToken, TypedEventHandler};
 use windows::Storage::FileAccessMode;
+use windows::Win32::Foundation::{ERROR_BAD_COMMAND, ERROR_DEVICE_NOT_CONNECTED};
 
-use crate::backend::winrt::utils::{IBufferExt, WinResultExt};
+use crate::backend::winrt::utils::{CancelOnDrop, IBufferExt, WinResultExt};
 use crate::error::{ErrorSource, HidResult};
-use crate::{ensure, AccessMode, DeviceInfo, HidError};
+use crate::timer::poll_once;
+use crate::{ensure, AccessMode, BusType, DeviceInfo, HidError, ReportIds, RetryPolicy, WindowsDeviceInfoExt};
 
+// Selects every enabled HID interface, including the ones of paired Bluetooth classic and Bluetooth LE devices
 const DEVICE_SELECTOR: &HSTRING = h!(
     r#"System.Devices.InterfaceClassGuid:="{4D1E55B2-F16F-11CF-88CB-001111000030}" AND System.Devices.InterfaceEnabled:=System.StructuredQueryType.Boolean#True"#
 );
@@ -33,8 +37,7 @@ pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + S
     //    .filter_map(|info| ready(info.ok()))
     //    .collect()
     //    .await;
-    let devices = DeviceInformation::FindAllAsyncAqsFilter(DEVICE_SELECTOR)?
-        .await?;
+    let devices = CancelOnDrop(DeviceInformation::FindAllAsyncAqsFilter(DEVICE_SELECTOR)?).await?;
     let devices = DeviceInformationSteam::from(devices)
         .then(|info| Box::pin(get_device_information(info)))
         .filter_map(|r| {
@@ -46,6 +49,19 @@ pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + S
     Ok(devices)
 }
 
+pub async fn enumerate_passive() -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send> {
+    // The device information is only available from an opened HidDevice
+    enumerate().await
+}
+
+pub async fn count() -> HidResult<usize> {
+    let devices = CancelOnDrop(DeviceInformation::FindAllAsyncAqsFilter(DEVICE_SELECTOR)?).await?;
+    Ok(devices.Size()? as usize)
+}
+
+pub async fn enumerate_with_retry(_policy: RetryPolicy) -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send> {
+    enumerate().await
+}
 
 //fn get_device_information_unpin(device: DeviceInformation) -> impl Future<Output = HidResult<DeviceInfo>> + Unpin {
 //
@@ -54,43 +70,61 @@ pub async fn enumerate() -> HidResult<impl Stream<Item = DeviceInfo> + Unpin + S
 async fn get_device_information(device: DeviceInformation) -> HidResult<DeviceInfo> {
     let id = device.Id()?;
     let name = device.Name()?.to_string_lossy();
-    let device = HidDevice::FromIdAsync(&id, FileAccessMode::Read)?;
+    let device = CancelOnDrop(HidDevice::FromIdAsync(&id, FileAccessMode::Read)?);
     let device = device
         .await
         .on_null_result(|| HidError::custom(format!("Failed to open {name} (Id: {id})")))?;
+    let usage_id = device.UsageId().unwrap_or_else(|e| default_usage(&name, e));
+    let usage_page = device.UsagePage().unwrap_or_else(|e| default_usage(&name, e));
     Ok(DeviceInfo {
         id: HashableHSTRING(id).into(),
         name,
         product_id: device.ProductId()?,
         vendor_id: device.VendorId()?,
-        usage_id: device.UsageId()?,
-        usage_page: device.UsagePage()?,
+        usage_id,
+        usage_page,
+        collections: Arc::new([(usage_page, usage_id)]),
         private_data: BackendPrivateData::default()
     })
 }
 
+/// Keeps devices whose usage can't be read instead of excluding them
+fn default_usage(name: &str, error: windows::core::Error) -> u16 {
+    log::trace!("Defaulting the usage of {name:?} to 0\n\tbecause {error:?}");
+    0
+}
+
 #[derive(Debug, Clone)]
 struct InputReceiver {
     buffer: Receiver<HidInputReport>,
-    token: EventRegistrationToken
+    token: EventRegistrationToken,
+    dropped: Arc<AtomicU64>
 }
 
 impl InputReceiver {
     fn new(device: &HidDevice) -> HidResult<Self> {
         let (sender, receiver) = flume::bounded(64);
         let drain = receiver.clone();
+        let dropped = Arc::new(AtomicU64::new(0));
+        let counter = dropped.clone();
         let token = device.InputReportReceived(&TypedEventHandler::new(move |_, args: &Option<HidInputReportReceivedEventArgs>| {
             if let Some(args) = args {
                 let mut msg = args.Report()?;
                 while let Err(TrySendError::Full(ret)) = sender.try_send(msg) {
                     log::trace!("Dropping previous input report because the queue is full");
-                    let _ = drain.try_recv();
+                    if drain.try_recv().is_ok() {
+                        counter.fetch_add(1, Ordering::Relaxed);
+                    }
                     msg = ret;
                 }
             }
             Ok(())
         }))?;
-        Ok(Self { buffer: receiver, token })
+        Ok(Self {
+            buffer: receiver,
+            token,
+            dropped
+        })
     }
 
     async fn recv_async(&self) -> HidInputReport {
@@ -132,6 +166,15 @@ pub async fn open(id: &BackendDeviceId, mode: AccessMode) -> HidResult<BackendDe
     Ok(BackendDevice { device, input })
 }
 
+pub fn string_descriptor(id: &BackendDeviceId, index: u8) -> HidResult<Option<String>> {
+    win32::get_indexed_string(id, index)
+}
+
+pub fn is_claimed(_id: &BackendDeviceId) -> bool {
+    // WinRT doesn't expose whether a device is in use by someone else
+    false
+}
+
 impl BackendDevice {
     pub async fn read_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
         let report = self
@@ -150,6 +193,21 @@ impl BackendDevice {
         Ok(size - start)
     }
 
+    pub fn try_read_input_report(&self, buf: &mut [u8]) -> HidResult<Option<usize>> {
+        poll_once(self.read_input_report(buf)).transpose()
+    }
+
+    pub async fn disconnected(&self) -> HidResult<()> {
+        // HidDevice doesn't report the removal of the device, it only shows up as failing operations
+        Err(HidError::unsupported())
+    }
+
+    pub fn dropped_reports(&self) -> u64 {
+        self.input
+            .as_ref()
+            .map_or(0, |input| input.dropped.load(Ordering::Relaxed))
+    }
+
     pub async fn write_output_report(&self, buf: &[u8]) -> HidResult<()> {
         ensure!(!buf.is_empty(), HidError::zero_sized_data());
         let report = self.device.CreateOutputReport()?;
@@ -165,18 +223,103 @@ impl BackendDevice {
         self.device.SendOutputReportAsync(&report)?.await?;
         Ok(())
     }
+
+    pub async fn set_output_report(&self, _buf: &[u8]) -> HidResult<()> {
+        // SendOutputReportAsync always picks the transport itself
+        Err(HidError::unsupported())
+    }
+
+    pub async fn flush(&self) -> HidResult<()> {
+        // Every write already awaits the completion of SendOutputReportAsync
+        Ok(())
+    }
+
+    pub async fn send_feature_report(&self, buf: &[u8]) -> HidResult<()> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        let report = self.device.CreateFeatureReportById(buf[0] as u16)?;
+
+        {
+            let mut buffer = report.Data()?;
+            ensure!(buffer.Length()? as usize >= buf.len(), HidError::custom("Feature report is too large"));
+            let (buffer, remainder) = buffer.as_mut_slice()?.split_at_mut(buf.len());
+            buffer.copy_from_slice(buf);
+            remainder.fill(0);
+        }
+
+        self.device.SendFeatureReportAsync(&report)?.await?;
+        Ok(())
+    }
+
+    pub async fn get_feature_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        let report = self.device.GetFeatureReportByIdAsync(buf[0] as u16)?.await?;
+        let buffer = report.Data()?;
+        let buffer = buffer.as_slice()?;
+        let size = buf.len().min(buffer.len());
+        buf[..size].copy_from_slice(&buffer[..size]);
+        Ok(size)
+    }
+
+    pub async fn get_output_report(&self, _buf: &mut [u8]) -> HidResult<usize> {
+        // Windows only allows reading input and feature reports
+        Err(HidError::unsupported())
+    }
+
+    pub async fn physical_descriptor(&self) -> HidResult<Option<Vec<u8>>> {
+        Err(HidError::unsupported())
+    }
+
+    pub async fn report_ids(&self) -> HidResult<ReportIds> {
+        Err(HidError::unsupported())
+    }
+
+    pub async fn report_descriptor(&self) -> HidResult<Vec<u8>> {
+        Err(HidError::unsupported())
+    }
 }
 
 #[derive(Default, Debug, Clone, Eq, PartialEq)]
 pub struct BackendPrivateData {
-    serial_number: OnceLock<Option<String>>
+    serial_number: OnceLock<Option<String>>,
+    manufacturer: OnceLock<Option<String>>
+}
+
+impl DeviceInfo {
+    /// The device interface id, which contains the port based instance id for devices without a serial number
+    pub(crate) fn physical_location(&self) -> Option<String> {
+        Some(self.interface_path())
+    }
+
+    pub(crate) fn topology(&self) -> Option<String> {
+        // The interface path doesn't contain the parent devices, they are only available through the configuration manager
+        None
+    }
+
+    pub(crate) fn interface_class(&self) -> Option<(u8, u8)> {
+        // Like the topology, the usb interface is only available through the configuration manager
+        None
+    }
+
+    pub(crate) fn connected_bus(&self) -> BusType {
+        // The interface path only tells Bluetooth devices apart, the bus of other devices is only available through the configuration manager
+        match self.is_bluetooth() {
+            true => BusType::Bluetooth,
+            false => BusType::Unknown
+        }
+    }
+}
+
+impl WindowsDeviceInfoExt for DeviceInfo {
+    fn interface_path(&self) -> String {
+        self.id.0.to_string()
+    }
 }
 
 /// Wrapper type for HSTRING to add Hash implementation
 ///
 /// windows-rs has a built-in Hash HSTRING implementation after version 0.55.0 (introduced by this PR https://github.com/microsoft/windows-rs/pull/2924/files)
 /// Though, a direct upgrade to the newer windows-rs versions would require further work due to API and functionality changes
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
 pub struct HashableHSTRING(HSTRING);
 
 impl Display for HashableHSTRING {
@@ -205,6 +348,9 @@ impl Hash for HashableHSTRING {
     }
 }
 
+/// SendOutputReportAsync keeps running after its future got dropped
+pub const DROPPED_WRITES_CONTINUE: bool = true;
+
 pub type BackendDeviceId = HashableHSTRING;
 pub type BackendError = windows::core::Error;
 
@@ -214,6 +360,12 @@ impl From<BackendError> for ErrorSource {
     }
 }
 
+pub fn is_disconnected(error: &BackendError) -> bool {
+    [ERROR_DEVICE_NOT_CONNECTED, ERROR_BAD_COMMAND]
+        .iter()
+        .any(|code| error.code() == HRESULT::from_win32(code.0))
+}
+
 impl From<AccessMode> for FileAccessMode {
     fn from(value: AccessMode) -> Self {
         match value {
@@ -1,6 +1,10 @@
+use std::future::Future;
+use std::pin::Pin;
 use std::slice::{from_raw_parts, from_raw_parts_mut};
+use std::task::{Context, Poll};
 
-use windows::core::{Interface, Result};
+use windows::core::{Interface, Result, RuntimeType};
+use windows::Foundation::{AsyncStatus, IAsyncOperation};
 use windows::Storage::Streams::IBuffer;
 use windows::Win32::System::WinRT::IBufferByteAccess;
 
@@ -43,3 +47,24 @@ impl<T> WinResultExt<T> for Result<T> {
         }
     }
 }
+
+/// Wrapper for an [IAsyncOperation] that cancels the operation if it gets dropped before completing
+pub struct CancelOnDrop<T: RuntimeType + 'static>(pub IAsyncOperation<T>);
+
+impl<T: RuntimeType + 'static> Future for CancelOnDrop<T> {
+    type Output = Result<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T: RuntimeType + 'static> Drop for CancelOnDrop<T> {
+    fn drop(&mut self) {
+        if matches!(self.0.Status(), Ok(AsyncStatus::Started)) {
+            if let Err(err) = self.0.Cancel() {
+                log::trace!("Failed to cancel async operation\n\tbecause {err:?}");
+            }
+        }
+    }
+}
@@ -1,13 +1,13 @@
 use std::mem::size_of;
 
 use windows::core::PCWSTR;
-use windows::Win32::Devices::HumanInterfaceDevice::HidD_GetSerialNumberString;
+use windows::Win32::Devices::HumanInterfaceDevice::{HidD_GetIndexedString, HidD_GetManufacturerString, HidD_GetSerialNumberString};
 use windows::Win32::Foundation::{CloseHandle, HANDLE};
 use windows::Win32::Storage::FileSystem::{CreateFileW, FILE_FLAG_OVERLAPPED, FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING};
 
 use crate::backend::BackendDeviceId;
 use crate::error::HidResult;
-use crate::{DeviceInfo, SerialNumberExt};
+use crate::{DeviceInfo, ManufacturerExt, SerialNumberExt};
 
 impl SerialNumberExt for DeviceInfo {
     fn serial_number(&self) -> Option<&str> {
@@ -23,6 +23,32 @@ impl SerialNumberExt for DeviceInfo {
     }
 }
 
+impl ManufacturerExt for DeviceInfo {
+    fn manufacturer(&self) -> Option<&str> {
+        self.private_data
+            .manufacturer
+            .get_or_init(|| {
+                get_manufacturer(&self.id.0)
+                    .map_err(|err| log::trace!("Failed to query additional information:\n\t{:?}", err))
+                    .ok()
+                    .filter(|s| !s.is_empty())
+            })
+            .as_deref()
+    }
+}
+
+fn get_manufacturer(path: &BackendDeviceId) -> HidResult<String> {
+    let handle = open_device(PCWSTR::from_raw(path.as_ptr()))?;
+    let mut buffer = [0u16; 256];
+    unsafe { HidD_GetManufacturerString(handle.as_raw(), buffer.as_mut_ptr() as _, (size_of::<u16>() * buffer.len()) as u32) }.ok()?;
+    let manufacturer = buffer
+        .split(|c| *c == 0x0)
+        .map(String::from_utf16_lossy)
+        .next()
+        .expect("Failed to interpret string");
+    Ok(manufacturer)
+}
+
 fn get_serial_number(path: &BackendDeviceId) -> HidResult<String> {
     let handle = open_device(PCWSTR::from_raw(path.as_ptr()))?;
     let mut buffer = [0u16; 256];
@@ -35,6 +61,22 @@ fn get_serial_number(path: &BackendDeviceId) -> HidResult<String> {
     Ok(serial_number)
 }
 
+pub fn get_indexed_string(path: &BackendDeviceId, index: u8) -> HidResult<Option<String>> {
+    let handle = open_device(PCWSTR::from_raw(path.as_ptr()))?;
+    let mut buffer = [0u16; 256];
+    // Windows fails the request for indices that don't exist, which can't be told apart from other failures
+    if let Err(err) = unsafe { HidD_GetIndexedString(handle.as_raw(), index as u32, buffer.as_mut_ptr() as _, (size_of::<u16>() * buffer.len()) as u32) }.ok() {
+        log::trace!("Failed to read string descriptor {index}\n\tbecause {err:?}");
+        return Ok(None);
+    }
+    let string = buffer
+        .split(|c| *c == 0x0)
+        .map(String::from_utf16_lossy)
+        .next()
+        .expect("Failed to interpret string");
+    Ok((!string.is_empty()).then_some(string))
+}
+
 fn open_device(path: PCWSTR) -> HidResult<Handle> {
     let handle = unsafe {
         CreateFileW(
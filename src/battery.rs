@@ -0,0 +1,34 @@
+//! Helpers for devices that report their battery level
+
+use crate::{Device, HidResult};
+
+/// Generic Device Controls: Battery Strength
+const BATTERY_STRENGTH: u32 = 0x0006_0020;
+/// Battery System: Relative State Of Charge
+const RELATIVE_STATE_OF_CHARGE: u32 = 0x0085_0064;
+/// Battery System: Absolute State Of Charge
+const ABSOLUTE_STATE_OF_CHARGE: u32 = 0x0085_0065;
+
+impl Device {
+    /// Reads the battery level of this device in percent
+    ///
+    /// The level is taken from the first battery usage that the report descriptor declares, preferring feature reports.
+    /// If the usage is only part of an input report this waits for the next input report with the matching report id,
+    /// discarding all other input reports in the meantime.
+    /// Returns `None` if the device doesn't declare a battery usage.
+    ///
+    /// This requires the report descriptor, which is currently only available on Linux and macOS.
+    pub async fn battery_level(&self) -> HidResult<Option<u8>> {
+        for usage in [BATTERY_STRENGTH, RELATIVE_STATE_OF_CHARGE, ABSOLUTE_STATE_OF_CHARGE] {
+            if let Some((field, value)) = self.value(usage).await? {
+                let (min, max) = (field.logical_minimum as i64, field.logical_maximum as i64);
+                let percent = match max > min {
+                    true => (value as i64 - min) * 100 / (max - min),
+                    false => value as i64
+                };
+                return Ok(Some(percent.clamp(0, 100) as u8));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,53 @@
+//! A process wide cache for the parsed report descriptors and report ids of devices
+//!
+//! The entries are keyed by the [id](crate::DeviceInfo::id) and the [fingerprint](crate::DeviceInfo::fingerprint) of a device,
+//! so reopening the same device doesn't retrieve and parse the descriptor again.
+//! The fingerprint alone isn't unique for devices without a serial number or physical location (i.e virtual devices and clones).
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{DeviceId, DeviceInfo, ReportIds, ReportLayout};
+
+/// Identifies a device in the cache
+pub(crate) type CacheKey = (DeviceId, u64);
+
+pub(crate) fn key(info: &DeviceInfo) -> CacheKey {
+    (info.id.clone(), info.fingerprint())
+}
+
+#[derive(Default)]
+struct Entry {
+    layout: Option<Arc<ReportLayout>>,
+    report_ids: Option<ReportIds>
+}
+
+static CACHE: Mutex<Option<HashMap<CacheKey, Entry>>> = Mutex::new(None);
+
+fn entries() -> MutexGuard<'static, Option<HashMap<CacheKey, Entry>>> {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub(crate) fn layout(key: &CacheKey) -> Option<Arc<ReportLayout>> {
+    entries().as_ref()?.get(key)?.layout.clone()
+}
+
+pub(crate) fn insert_layout(key: CacheKey, layout: Arc<ReportLayout>) {
+    entries().get_or_insert_with(HashMap::new).entry(key).or_default().layout = Some(layout);
+}
+
+pub(crate) fn report_ids(key: &CacheKey) -> Option<ReportIds> {
+    entries().as_ref()?.get(key)?.report_ids.clone()
+}
+
+pub(crate) fn insert_report_ids(key: CacheKey, report_ids: ReportIds) {
+    entries().get_or_insert_with(HashMap::new).entry(key).or_default().report_ids = Some(report_ids);
+}
+
+/// Removes all cached report descriptors and report ids
+///
+/// The cache assumes that the descriptor of a device never changes, which may not hold after a firmware update.
+/// Devices that are currently open keep using the descriptor they already retrieved.
+pub fn clear_descriptor_cache() {
+    *entries() = None;
+}
@@ -0,0 +1,65 @@
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, JoinHandle, Thread};
+
+use futures_core::Stream;
+
+use crate::{Device, HidResult};
+
+impl Device {
+    /// Reads input reports on a dedicated thread and passes every report to the callback
+    ///
+    /// Reading stops once the callback returns `false` or the device got disconnected (see [Device::into_input_reports]).
+    /// Failed reads are passed to the callback as well, so it can decide whether to continue.
+    /// The device is closed when the thread finishes.
+    ///
+    /// With the `tokio` feature the reads are driven by the Tokio runtime this is called from, which the thread enters.
+    /// That runtime has to keep running while the thread reads, i.e a multi-threaded runtime or a current-thread runtime that is polled by another thread.
+    pub fn read_with_callback(self, mut cb: impl FnMut(HidResult<&[u8]>) -> bool + Send + 'static) -> JoinHandle<()> {
+        let buffer_size = self.input_buffer_size();
+        let mut reports = self.into_input_reports(buffer_size);
+        #[cfg(feature = "tokio")]
+        let runtime = tokio::runtime::Handle::try_current()
+            .map_err(|e| log::trace!("Reading on a thread without a Tokio runtime\n\tbecause {e:?}"))
+            .ok();
+        thread::Builder::new()
+            .name("async-hid-reader".into())
+            .spawn(move || {
+                #[cfg(feature = "tokio")]
+                let _runtime = runtime.as_ref().map(|handle| handle.enter());
+                while let Some(report) = block_on(poll_fn(|cx| Pin::new(&mut reports).poll_next(cx))) {
+                    let proceed = match report {
+                        Ok(report) => cb(Ok(&report)),
+                        Err(e) => cb(Err(e))
+                    };
+                    if !proceed {
+                        break;
+                    }
+                }
+            })
+            .expect("Failed to spawn the reader thread")
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs the future on the current thread, parking it while the future is pending
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park()
+        }
+    }
+}
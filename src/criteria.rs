@@ -0,0 +1,122 @@
+use crate::{DeviceInfo, ManufacturerExt, SerialNumberExt};
+
+/// A set of optional constraints that a device must satisfy
+///
+/// Fields that are `None` match every device.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct DeviceCriteria {
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    /// Only matches devices whose product id is in the list, i.e all products of a family
+    pub product_ids: Option<Vec<u16>>,
+    pub usage_page: Option<u16>,
+    pub usage_id: Option<u16>,
+    /// Devices without a serial number never match if this is set
+    pub serial_number: Option<String>,
+    /// Devices without a manufacturer string never match if this is set
+    pub manufacturer: Option<String>,
+    /// Only matches devices below this [topology path](DeviceInfo::topology_path), i.e all devices connected to a hub
+    ///
+    /// Devices whose position is unknown never match if this is set.
+    /// The topology path isn't available on Windows, so this never matches any device there.
+    pub topology_prefix: Option<String>
+}
+
+impl DeviceCriteria {
+    /// Creates a builder for constraining the devices step by step
+    ///
+    /// ```no_run
+    /// # use async_hid::DeviceCriteria;
+    /// let criteria = DeviceCriteria::builder().vendor_id(0x046D).usage_page(0x01).build();
+    /// ```
+    pub fn builder() -> DeviceCriteriaBuilder {
+        DeviceCriteriaBuilder::default()
+    }
+
+    /// Creates criteria that match all devices of the given vendor with one of the given product ids
+    ///
+    /// ```no_run
+    /// # use async_hid::DeviceCriteria;
+    /// let criteria = DeviceCriteria::family(0x046D, &[0xC52B, 0xC534, 0xC548]);
+    /// ```
+    pub fn family(vendor_id: u16, product_ids: &[u16]) -> Self {
+        Self::builder().vendor_id(vendor_id).product_ids(product_ids.iter().copied()).build()
+    }
+
+    /// Checks if the given device satisfies all constraints
+    pub fn matches(&self, info: &DeviceInfo) -> bool {
+        self.vendor_id.is_none_or(|id| id == info.vendor_id)
+            && self.product_id.is_none_or(|id| id == info.product_id)
+            && self
+                .product_ids
+                .as_deref()
+                .is_none_or(|ids| ids.contains(&info.product_id))
+            && self.usage_page.is_none_or(|page| page == info.usage_page)
+            && self.usage_id.is_none_or(|id| id == info.usage_id)
+            && self
+                .serial_number
+                .as_deref()
+                .is_none_or(|serial| info.serial_number() == Some(serial))
+            && self
+                .manufacturer
+                .as_deref()
+                .is_none_or(|manufacturer| info.manufacturer() == Some(manufacturer))
+            && self
+                .topology_prefix
+                .as_deref()
+                .is_none_or(|prefix| info.is_below(prefix))
+    }
+}
+
+/// Builder for [DeviceCriteria]
+#[derive(Debug, Default, Clone)]
+pub struct DeviceCriteriaBuilder {
+    criteria: DeviceCriteria
+}
+
+impl DeviceCriteriaBuilder {
+    pub fn vendor_id(mut self, vendor_id: u16) -> Self {
+        self.criteria.vendor_id = Some(vendor_id);
+        self
+    }
+
+    pub fn product_id(mut self, product_id: u16) -> Self {
+        self.criteria.product_id = Some(product_id);
+        self
+    }
+
+    pub fn product_ids(mut self, product_ids: impl IntoIterator<Item = u16>) -> Self {
+        self.criteria.product_ids = Some(product_ids.into_iter().collect());
+        self
+    }
+
+    pub fn usage_page(mut self, usage_page: u16) -> Self {
+        self.criteria.usage_page = Some(usage_page);
+        self
+    }
+
+    pub fn usage_id(mut self, usage_id: u16) -> Self {
+        self.criteria.usage_id = Some(usage_id);
+        self
+    }
+
+    pub fn serial_number(mut self, serial_number: impl Into<String>) -> Self {
+        self.criteria.serial_number = Some(serial_number.into());
+        self
+    }
+
+    pub fn manufacturer(mut self, manufacturer: impl Into<String>) -> Self {
+        self.criteria.manufacturer = Some(manufacturer.into());
+        self
+    }
+
+    /// See [DeviceCriteria::topology_prefix], which never matches on Windows
+    pub fn topology_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.criteria.topology_prefix = Some(prefix.into());
+        self
+    }
+
+    pub fn build(self) -> DeviceCriteria {
+        self.criteria
+    }
+}
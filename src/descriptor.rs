@@ -0,0 +1,527 @@
+//! A minimal parser for HID report descriptors (see chapter 6.2.2 of the HID specification)
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::{ensure, HidError, HidResult};
+
+/// The report ids declared by a device, grouped by the type of the report
+///
+/// Devices that don't use numbered reports (conceptually report id 0) return empty sets.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ReportIds {
+    pub input: BTreeSet<u8>,
+    pub output: BTreeSet<u8>,
+    pub feature: BTreeSet<u8>
+}
+
+impl ReportIds {
+    pub(crate) fn from_descriptor(descriptor: &[u8]) -> Self {
+        let mut result = Self::default();
+        let mut report_id = 0u8;
+        let mut stack = Vec::new();
+        for item in items(descriptor) {
+            match item.tag {
+                // Report ID 6.2.2.7 (Global)
+                0x84 => report_id = item.unsigned() as u8,
+                // Push 6.2.2.7 (Global)
+                0xa4 => stack.push(report_id),
+                // Pop 6.2.2.7 (Global)
+                0xb4 => report_id = stack.pop().unwrap_or_default(),
+                // Input 6.2.2.4 (Main)
+                0x80 if report_id != 0 => {
+                    result.input.insert(report_id);
+                }
+                // Output 6.2.2.4 (Main)
+                0x90 if report_id != 0 => {
+                    result.output.insert(report_id);
+                }
+                // Feature 6.2.2.4 (Main)
+                0xb0 if report_id != 0 => {
+                    result.feature.insert(report_id);
+                }
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Checks that the first byte of an output report is a valid report id for this device
+    ///
+    /// Report id 0 means that the remaining bytes are the whole report, which is only valid for devices without numbered output reports.
+    pub(crate) fn check_output_report(&self, buf: &[u8]) -> HidResult<()> {
+        let report_id = *buf.first().ok_or_else(|| HidError::zero_sized_data())?;
+        ensure!(
+            report_id != 0 || self.output.is_empty(),
+            HidError::custom("Report id 0 is reserved for devices without numbered reports")
+        );
+        Ok(())
+    }
+}
+
+/// A single item of a report descriptor
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Item<'a> {
+    /// The prefix of a short item without the size bits (i.e `0x04` for Usage Page) or `0xfe` for long items
+    pub tag: u8,
+    pub data: &'a [u8]
+}
+
+impl Item<'_> {
+    /// Interprets the data of this item as an unsigned little endian integer
+    pub fn unsigned(&self) -> u32 {
+        self.data
+            .iter()
+            .take(4)
+            .rev()
+            .fold(0, |acc, b| (acc << 8) | *b as u32)
+    }
+
+    /// Interprets the data of this item as a signed little endian integer
+    pub fn signed(&self) -> i32 {
+        match self.data.len() {
+            1 => self.unsigned() as u8 as i8 as i32,
+            2 => self.unsigned() as u16 as i16 as i32,
+            _ => self.unsigned() as i32
+        }
+    }
+
+    /// Interprets the data of this item as a usage, which contains the usage page if it's four bytes long
+    pub fn usage(&self, usage_page: u16) -> u32 {
+        match self.data.len() {
+            4 => self.unsigned(),
+            _ => (usage_page as u32) << 16 | self.unsigned()
+        }
+    }
+}
+
+/// Iterates over all items of the descriptor
+///
+/// Iteration stops at the first truncated item.
+pub(crate) fn items(descriptor: &[u8]) -> impl Iterator<Item = Item<'_>> {
+    let mut remaining = descriptor;
+    std::iter::from_fn(move || {
+        let (&prefix, rest) = remaining.split_first()?;
+        let (tag, header, size) = match prefix {
+            // Long Item. The next byte contains the length of the data section, followed by the actual tag
+            0xfe => (prefix, 3, *rest.first()? as usize),
+            _ => (prefix & 0xfc, 1, [0, 1, 2, 4][(prefix & 0x03) as usize])
+        };
+        let data = remaining.get(header..header + size)?;
+        remaining = &remaining[header + size..];
+        Some(Item { tag, data })
+    })
+}
+
+/// The type of a report
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ReportType {
+    Input,
+    Output,
+    Feature
+}
+
+/// A group of values inside a report, as declared by a single main item of the report descriptor
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ReportField {
+    pub report_type: ReportType,
+    /// The report id of the containing report or `0x0` if the device does not use numbered reports
+    pub report_id: u8,
+    /// The offset of the first value in bits, counted from the first byte after the report id
+    pub bit_offset: usize,
+    /// The size of a single value in bits
+    pub bit_size: usize,
+    /// The number of values
+    pub count: usize,
+    /// The extended usage of the top-level collection that contains the field
+    pub collection: u32,
+    /// The extended usages (usage page in the upper 16 bits) declared for the values
+    ///
+    /// For array fields the value is an index into this list instead.
+    pub usages: Vec<u32>,
+    pub logical_minimum: i32,
+    pub logical_maximum: i32,
+    /// The raw data of the main item (i.e `0x02` for Data, Variable, Absolute)
+    pub flags: u32
+}
+
+impl ReportField {
+    /// Constant fields only contain padding
+    pub fn is_constant(&self) -> bool {
+        self.flags & 0x01 != 0
+    }
+
+    /// Variable fields contain one value per usage, array fields contain indices of the active usages
+    pub fn is_variable(&self) -> bool {
+        self.flags & 0x02 != 0
+    }
+
+    /// Relative fields contain the change since the last report instead of an absolute value
+    pub fn is_relative(&self) -> bool {
+        self.flags & 0x04 != 0
+    }
+
+    /// The usage of the value at the given index of a variable field
+    pub fn usage(&self, index: usize) -> Option<u32> {
+        match index < self.count {
+            true => self.usages.get(index).or(self.usages.last()).copied(),
+            false => None
+        }
+    }
+
+    /// The index of the first value with the given extended usage
+    pub fn index_of(&self, usage: u32) -> Option<usize> {
+        (0..self.count).find(|i| self.usage(*i) == Some(usage))
+    }
+
+    /// Extracts the value at the given index from the report data (without the report id)
+    ///
+    /// Values are sign extended if the logical minimum is negative.
+    pub fn extract(&self, data: &[u8], index: usize) -> Option<i32> {
+        let start = self.value_offset(index, data.len())?;
+        let raw = (0..self.bit_size).fold(0u32, |acc, bit| {
+            let position = start + bit;
+            acc | (((data[position / 8] >> (position % 8)) & 1) as u32) << bit
+        });
+        let value = match self.logical_minimum < 0 && self.bit_size < 32 {
+            true => ((raw << (32 - self.bit_size)) as i32) >> (32 - self.bit_size),
+            false => raw as i32
+        };
+        Some(value)
+    }
+
+    /// The bit offset of the value at the given index if it fits into a report of the given length
+    fn value_offset(&self, index: usize, length: usize) -> Option<usize> {
+        let start = self.bit_offset + index * self.bit_size;
+        let valid = index < self.count && (1..=32).contains(&self.bit_size) && start + self.bit_size <= length * 8;
+        valid.then_some(start)
+    }
+
+    /// Stores the value at the given index in the report data (without the report id)
+    ///
+    /// Returns `false` if the report data is too short to contain the value.
+    pub fn insert(&self, data: &mut [u8], index: usize, value: i32) -> bool {
+        let Some(start) = self.value_offset(index, data.len()) else {
+            return false;
+        };
+        for bit in 0..self.bit_size {
+            let position = start + bit;
+            let mask = 1 << (position % 8);
+            match (value as u32 >> bit) & 1 {
+                1 => data[position / 8] |= mask,
+                _ => data[position / 8] &= !mask
+            }
+        }
+        true
+    }
+}
+
+/// The layout of all reports of a device
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ReportLayout {
+    pub fields: Vec<ReportField>
+}
+
+/// The global state of the parser (see 6.2.2.7)
+#[derive(Debug, Default, Copy, Clone)]
+struct GlobalState {
+    usage_page: u16,
+    logical_minimum: i32,
+    logical_maximum: i32,
+    report_size: usize,
+    report_count: usize,
+    report_id: u8
+}
+
+/// Upper bound for usage ranges to keep malformed descriptors from exhausting the memory
+const MAX_USAGE_RANGE: u32 = 0xffff;
+
+impl ReportLayout {
+    /// Parses the given report descriptor
+    ///
+    /// Unknown items are skipped and parsing stops at the first truncated item.
+    pub fn parse(descriptor: &[u8]) -> Self {
+        let mut fields = Vec::new();
+        let mut global = GlobalState::default();
+        let mut stack = Vec::new();
+        let mut usages = Vec::new();
+        let mut usage_minimum = None;
+        let mut offsets = HashMap::new();
+        let mut collection = 0;
+        let mut depth = 0usize;
+        for item in items(descriptor) {
+            match item.tag {
+                // Input, Output, Feature 6.2.2.4 (Main)
+                0x80 | 0x90 | 0xb0 => {
+                    let report_type = match item.tag {
+                        0x80 => ReportType::Input,
+                        0x90 => ReportType::Output,
+                        _ => ReportType::Feature
+                    };
+                    let offset: &mut usize = offsets.entry((report_type, global.report_id)).or_default();
+                    fields.push(ReportField {
+                        report_type,
+                        report_id: global.report_id,
+                        bit_offset: *offset,
+                        bit_size: global.report_size,
+                        count: global.report_count,
+                        collection,
+                        usages: std::mem::take(&mut usages),
+                        logical_minimum: global.logical_minimum,
+                        logical_maximum: global.logical_maximum,
+                        flags: item.unsigned()
+                    });
+                    *offset += global.report_size * global.report_count;
+                    usage_minimum = None;
+                }
+                // Collection 6.2.2.4 (Main)
+                0xa0 => {
+                    if depth == 0 {
+                        collection = usages.first().copied().unwrap_or_default();
+                    }
+                    depth += 1;
+                    usages.clear();
+                    usage_minimum = None;
+                }
+                // End Collection 6.2.2.4 (Main)
+                0xc0 => {
+                    depth = depth.saturating_sub(1);
+                    usages.clear();
+                    usage_minimum = None;
+                }
+                // Usage Page 6.2.2.7 (Global)
+                0x04 => global.usage_page = item.unsigned() as u16,
+                // Logical Minimum 6.2.2.7 (Global)
+                0x14 => global.logical_minimum = item.signed(),
+                // Logical Maximum 6.2.2.7 (Global)
+                0x24 => {
+                    global.logical_maximum = match global.logical_minimum < 0 {
+                        true => item.signed(),
+                        false => item.unsigned() as i32
+                    }
+                }
+                // Report Size 6.2.2.7 (Global)
+                0x74 => global.report_size = item.unsigned() as usize,
+                // Report ID 6.2.2.7 (Global)
+                0x84 => global.report_id = item.unsigned() as u8,
+                // Report Count 6.2.2.7 (Global)
+                0x94 => global.report_count = item.unsigned() as usize,
+                // Push 6.2.2.7 (Global)
+                0xa4 => stack.push(global),
+                // Pop 6.2.2.7 (Global)
+                0xb4 => global = stack.pop().unwrap_or_default(),
+                // Usage 6.2.2.8 (Local)
+                0x08 => usages.push(item.usage(global.usage_page)),
+                // Usage Minimum 6.2.2.8 (Local)
+                0x18 => usage_minimum = Some(item.usage(global.usage_page)),
+                // Usage Maximum 6.2.2.8 (Local)
+                0x28 => {
+                    if let Some(minimum) = usage_minimum.take() {
+                        let maximum = item.usage(global.usage_page).min(minimum.saturating_add(MAX_USAGE_RANGE));
+                        usages.extend(minimum..=maximum);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Self { fields }
+    }
+
+    /// Finds the first field that contains the given extended usage and returns it together with the index of the value
+    pub fn find(&self, report_type: ReportType, usage: u32) -> Option<(&ReportField, usize)> {
+        self.fields
+            .iter()
+            .filter(|field| field.report_type == report_type && field.is_variable())
+            .find_map(|field| field.index_of(usage).map(|index| (field, index)))
+    }
+
+    /// The ids of the reports of the given type that belong to the top-level collection with the given extended usage
+    pub fn collection_report_ids(&self, report_type: ReportType, collection: u32) -> BTreeSet<u8> {
+        self.fields
+            .iter()
+            .filter(|field| field.report_type == report_type && field.collection == collection)
+            .map(|field| field.report_id)
+            .collect()
+    }
+
+    /// Whether the reports of the given type start with a report id
+    pub fn is_numbered(&self, report_type: ReportType) -> bool {
+        self.fields
+            .iter()
+            .any(|field| field.report_type == report_type && field.report_id != 0)
+    }
+
+    /// The length of the given report in bytes, excluding the report id
+    pub fn report_length(&self, report_type: ReportType, report_id: u8) -> usize {
+        let bits = self
+            .fields
+            .iter()
+            .filter(|field| field.report_type == report_type && field.report_id == report_id)
+            .map(|field| field.bit_offset + field.bit_size * field.count)
+            .max()
+            .unwrap_or_default();
+        bits.div_ceil(8)
+    }
+
+    /// The length of the longest report of the given type in bytes, including the report id of numbered reports
+    pub fn max_report_length(&self, report_type: ReportType) -> usize {
+        let numbered = self.is_numbered(report_type) as usize;
+        self.fields
+            .iter()
+            .filter(|field| field.report_type == report_type)
+            .map(|field| self.report_length(report_type, field.report_id) + numbered)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A boot keyboard without report ids
+    pub(crate) const KEYBOARD: &[u8] = &[
+        0x05, 0x01, // Usage Page (Generic Desktop)
+        0x09, 0x06, // Usage (Keyboard)
+        0xa1, 0x01, // Collection (Application)
+        0x05, 0x07, //   Usage Page (Keyboard)
+        0x19, 0xe0, //   Usage Minimum (Left Control)
+        0x29, 0xe7, //   Usage Maximum (Right GUI)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x25, 0x01, //   Logical Maximum (1)
+        0x75, 0x01, //   Report Size (1)
+        0x95, 0x08, //   Report Count (8)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0x75, 0x08, //   Report Size (8)
+        0x95, 0x06, //   Report Count (6)
+        0x26, 0xff, 0x00, //   Logical Maximum (255)
+        0x19, 0x00, //   Usage Minimum (0)
+        0x2a, 0xff, 0x00, //   Usage Maximum (255)
+        0x81, 0x00, //   Input (Data, Array, Absolute)
+        0x05, 0x08, //   Usage Page (LEDs)
+        0x19, 0x01, //   Usage Minimum (Num Lock)
+        0x29, 0x05, //   Usage Maximum (Kana)
+        0x25, 0x01, //   Logical Maximum (1)
+        0x75, 0x01, //   Report Size (1)
+        0x95, 0x05, //   Report Count (5)
+        0x91, 0x02, //   Output (Data, Variable, Absolute)
+        0x75, 0x03, //   Report Size (3)
+        0x95, 0x01, //   Report Count (1)
+        0x91, 0x01, //   Output (Constant)
+        0xc0 // End Collection
+    ];
+
+    /// A consumer control with numbered input and feature reports, report 3 is declared between Push and Pop
+    pub(crate) const NUMBERED: &[u8] = &[
+        0x05, 0x0c, // Usage Page (Consumer)
+        0x09, 0x01, // Usage (Consumer Control)
+        0xa1, 0x01, // Collection (Application)
+        0x85, 0x01, //   Report ID (1)
+        0x75, 0x10, //   Report Size (16)
+        0x95, 0x02, //   Report Count (2)
+        0x19, 0x00, //   Usage Minimum (0)
+        0x2a, 0xff, 0x03, //   Usage Maximum (0x3FF)
+        0x81, 0x00, //   Input (Data, Array, Absolute)
+        0x85, 0x02, //   Report ID (2)
+        0x75, 0x08, //   Report Size (8)
+        0x95, 0x03, //   Report Count (3)
+        0x09, 0xe9, //   Usage (Volume Increment)
+        0xb1, 0x02, //   Feature (Data, Variable, Absolute)
+        0xa4, //   Push
+        0x85, 0x03, //   Report ID (3)
+        0x95, 0x01, //   Report Count (1)
+        0x09, 0xea, //   Usage (Volume Decrement)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0xb4, //   Pop
+        0x09, 0xe2, //   Usage (Mute)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0xc0 // End Collection
+    ];
+
+    /// Report 1 is numbered, the trailing unnumbered report is delivered with id 0 by the OS
+    const MIXED: &[u8] = &[
+        0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+        0x09, 0x01, // Usage (0x01)
+        0xa1, 0x01, // Collection (Application)
+        0x75, 0x08, //   Report Size (8)
+        0x85, 0x01, //   Report ID (1)
+        0x95, 0x04, //   Report Count (4)
+        0x09, 0x02, //   Usage (0x02)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0x85, 0x00, //   Report ID (0)
+        0x95, 0x02, //   Report Count (2)
+        0x09, 0x03, //   Usage (0x03)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0xc0 // End Collection
+    ];
+
+    #[test]
+    fn mixed_numbered_and_unnumbered_input_reports() {
+        // A non-empty set makes the win32 backend keep the id 0 of the unnumbered report
+        let report_ids = ReportIds::from_descriptor(MIXED);
+        assert_eq!(report_ids.input, BTreeSet::from([1]));
+
+        let layout = ReportLayout::parse(MIXED);
+        assert!(layout.is_numbered(ReportType::Input));
+        assert_eq!(layout.report_length(ReportType::Input, 1), 4);
+        assert_eq!(layout.report_length(ReportType::Input, 0), 2);
+        assert_eq!(layout.max_report_length(ReportType::Input), 5);
+    }
+
+    #[test]
+    fn unnumbered_reports() {
+        assert_eq!(ReportIds::from_descriptor(KEYBOARD), ReportIds::default());
+
+        let layout = ReportLayout::parse(KEYBOARD);
+        assert!(!layout.is_numbered(ReportType::Input));
+        assert!(layout.fields.iter().all(|field| field.report_id == 0 && field.collection == 0x0001_0006));
+        // 8 × 1 bit modifiers followed by 6 × 8 bit keys
+        assert_eq!(layout.report_length(ReportType::Input, 0), 7);
+        assert_eq!(layout.max_report_length(ReportType::Input), 7);
+        // 5 × 1 bit leds padded by 1 × 3 bits
+        assert_eq!(layout.report_length(ReportType::Output, 0), 1);
+        assert_eq!(layout.report_length(ReportType::Feature, 0), 0);
+
+        let keys = &layout.fields[1];
+        assert_eq!((keys.bit_offset, keys.bit_size, keys.count), (8, 8, 6));
+        assert!(!keys.is_variable());
+        assert_eq!(layout.find(ReportType::Input, 0x0007_00e1).map(|(field, index)| (field.bit_offset, index)), Some((0, 1)));
+        assert!(layout.fields[3].is_constant());
+        assert_eq!(layout.fields[3].bit_offset, 5);
+    }
+
+    #[test]
+    fn numbered_reports() {
+        let report_ids = ReportIds::from_descriptor(NUMBERED);
+        assert_eq!(report_ids.input, BTreeSet::from([1, 2, 3]));
+        assert_eq!(report_ids.feature, BTreeSet::from([2]));
+        assert!(report_ids.output.is_empty());
+
+        let layout = ReportLayout::parse(NUMBERED);
+        assert!(layout.is_numbered(ReportType::Input));
+        assert!(!layout.is_numbered(ReportType::Output));
+        // 2 × 16 bits
+        assert_eq!(layout.report_length(ReportType::Input, 1), 4);
+        // Report Count 3 is restored by Pop
+        assert_eq!(layout.report_length(ReportType::Input, 2), 3);
+        assert_eq!(layout.report_length(ReportType::Input, 3), 1);
+        assert_eq!(layout.report_length(ReportType::Feature, 2), 3);
+        assert_eq!(layout.max_report_length(ReportType::Input), 5);
+        assert_eq!(layout.collection_report_ids(ReportType::Input, 0x000c_0001), BTreeSet::from([1, 2, 3]));
+    }
+
+    #[test]
+    fn report_id_0_output_reports() {
+        // Devices without numbered output reports take report id 0 followed by the whole report
+        let unnumbered = ReportIds::from_descriptor(KEYBOARD);
+        assert!(unnumbered.check_output_report(&[0x00, 0x01]).is_ok());
+
+        // Report id 0 is reserved on devices with numbered output reports
+        let numbered = ReportIds {
+            output: BTreeSet::from([1]),
+            ..Default::default()
+        };
+        assert!(numbered.check_output_report(&[0x00, 0x01]).is_err());
+        assert!(numbered.check_output_report(&[0x01, 0x01]).is_ok());
+        assert!(numbered.check_output_report(&[]).is_err());
+    }
+}
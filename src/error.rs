@@ -1,9 +1,12 @@
 use std::borrow::Cow;
 use std::error::Error;
 use std::fmt::{Debug, Display, Formatter};
+use std::cell::Cell;
 use std::panic::Location;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
 
-use crate::backend::BackendError;
+use crate::backend::{is_disconnected, BackendError};
 
 pub type HidResult<T> = Result<T, HidError>;
 
@@ -11,6 +14,28 @@ pub type HidResult<T> = Result<T, HidError>;
 pub enum ErrorSource {
     PlatformSpecific(BackendError),
     InvalidZeroSizeData,
+    Unsupported,
+    /// The device did not send any input report within the configured time span
+    Stalled,
+    /// No device matched the given criteria
+    NotFound,
+    /// The device is opened exclusively by another process
+    Busy,
+    /// The operation did not complete within the given time span
+    Timeout,
+    /// The OS denied access to the device, the message describes which permission is missing
+    PermissionDenied(Cow<'static, str>),
+    /// The length of a received report differs from the length declared by the report descriptor
+    LengthMismatch {
+        report_id: u8,
+        expected: usize,
+        actual: usize
+    },
+    /// A received report is shorter than the length the caller requires
+    ShortReport {
+        got: usize,
+        expected: usize
+    },
     Custom(Cow<'static, str>)
 }
 
@@ -19,20 +44,130 @@ pub struct HidError {
     source: ErrorSource
 }
 
+type ErrorHook = Arc<dyn Fn(&HidError) + Send + Sync>;
+
+static HOOK_SET: AtomicBool = AtomicBool::new(false);
+static HOOK: RwLock<Option<ErrorHook>> = RwLock::new(None);
+
+thread_local! {
+    static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Marks the current thread as running the hook until it is dropped, even if the hook panics
+struct InHookGuard;
+
+impl InHookGuard {
+    fn enter() -> Option<Self> {
+        (!IN_HOOK.replace(true)).then_some(Self)
+    }
+}
+
+impl Drop for InHookGuard {
+    fn drop(&mut self) {
+        IN_HOOK.set(false);
+    }
+}
+
+/// Registers a function that is called with every [HidError] created by this library, i.e for central logging
+///
+/// This replaces the previously registered hook. Errors that are created while the hook runs don't invoke the hook again.
+/// The hook also sees errors that the library handles internally (i.e when falling back to another way of querying a property),
+/// so not every error passed to it reaches the caller.
+/// Without a hook the only overhead of creating an error is a single atomic load.
+pub fn set_error_hook(hook: impl Fn(&HidError) + Send + Sync + 'static) {
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(hook));
+    HOOK_SET.store(true, Ordering::Release);
+}
+
+/// Removes the hook registered with [set_error_hook]
+pub(crate) fn clear_error_hook() {
+    HOOK_SET.store(false, Ordering::Release);
+    *HOOK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
 impl HidError {
     #[track_caller]
-    pub fn custom(msg: impl Into<Cow<'static, str>>) -> Self {
-        Self {
+    fn new(source: ErrorSource) -> Self {
+        let error = Self {
             location: Location::caller(),
-            source: ErrorSource::Custom(msg.into())
+            source
+        };
+        if HOOK_SET.load(Ordering::Acquire) {
+            if let Some(_guard) = InHookGuard::enter() {
+                // The hook is called without holding the lock, so it may replace or remove itself
+                let hook = HOOK.read().unwrap_or_else(|e| e.into_inner()).clone();
+                if let Some(hook) = hook {
+                    hook(&error);
+                }
+            }
         }
+        error
+    }
+
+    #[track_caller]
+    pub fn custom(msg: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorSource::Custom(msg.into()))
     }
 
     #[track_caller]
     pub fn zero_sized_data() -> Self {
-        Self {
-            location: Location::caller(),
-            source: ErrorSource::InvalidZeroSizeData
+        Self::new(ErrorSource::InvalidZeroSizeData)
+    }
+
+    #[track_caller]
+    pub fn unsupported() -> Self {
+        Self::new(ErrorSource::Unsupported)
+    }
+
+    #[track_caller]
+    pub fn stalled() -> Self {
+        Self::new(ErrorSource::Stalled)
+    }
+
+    #[track_caller]
+    pub fn not_found() -> Self {
+        Self::new(ErrorSource::NotFound)
+    }
+
+    #[track_caller]
+    pub fn busy() -> Self {
+        Self::new(ErrorSource::Busy)
+    }
+
+    #[track_caller]
+    pub fn timeout() -> Self {
+        Self::new(ErrorSource::Timeout)
+    }
+
+    #[track_caller]
+    pub fn permission_denied(msg: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(ErrorSource::PermissionDenied(msg.into()))
+    }
+
+    #[track_caller]
+    pub fn length_mismatch(report_id: u8, expected: usize, actual: usize) -> Self {
+        Self::new(ErrorSource::LengthMismatch { report_id, expected, actual })
+    }
+
+    #[track_caller]
+    pub fn short_report(got: usize, expected: usize) -> Self {
+        Self::new(ErrorSource::ShortReport { got, expected })
+    }
+
+    /// The underlying cause of this error
+    pub fn error_source(&self) -> &ErrorSource {
+        &self.source
+    }
+
+    /// Whether this error was caused by the device being disconnected
+    ///
+    /// Disconnects are fatal, the device has to be reopened (see [Device::reconnect](crate::Device::reconnect)).
+    /// All other errors only affect the operation that failed.
+    /// On macOS disconnects can't be detected and this always returns `false`.
+    pub fn is_disconnected(&self) -> bool {
+        match &self.source {
+            ErrorSource::PlatformSpecific(error) => is_disconnected(error),
+            _ => false
         }
     }
 }
@@ -54,10 +189,7 @@ impl Error for HidError {}
 impl<T: Into<ErrorSource>> From<T> for HidError {
     #[track_caller]
     fn from(value: T) -> Self {
-        Self {
-            location: Location::caller(),
-            source: value.into()
-        }
+        Self::new(value.into())
     }
 }
 
@@ -70,3 +202,34 @@ macro_rules! ensure {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::thread;
+
+    use super::*;
+    use crate::ReportIds;
+
+    #[test]
+    fn hook_only_sees_returned_errors() {
+        // Other tests create errors concurrently, so only the errors of this thread are counted
+        let count = Arc::new(AtomicUsize::new(0));
+        let thread = thread::current().id();
+        set_error_hook({
+            let count = count.clone();
+            move |_| {
+                if thread::current().id() == thread {
+                    count.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+
+        assert!(ReportIds::default().check_output_report(&[0x00, 0x01]).is_ok());
+        assert_eq!(count.load(Ordering::Relaxed), 0);
+
+        assert!(ReportIds::default().check_output_report(&[]).is_err());
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        clear_error_hook();
+    }
+}
@@ -0,0 +1,99 @@
+//! Decoding of the standard gamepad usages (Generic Desktop page `0x01` and Button page `0x09`)
+
+use crate::{ensure, Device, HidError, HidResult, ReportField, ReportLayout, ReportType};
+
+/// Generic Desktop: X, Y, Z, Rx, Ry and Rz
+const AXES: [u32; 6] = [0x0001_0030, 0x0001_0031, 0x0001_0032, 0x0001_0033, 0x0001_0034, 0x0001_0035];
+/// Generic Desktop: Hat Switch
+const HAT_SWITCH: u32 = 0x0001_0039;
+const BUTTON_PAGE: u32 = 0x09;
+
+/// The state of a gamepad or joystick as reported by a single input report
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct GamepadState {
+    /// The X, Y, Z, Rx, Ry and Rz axes normalized to `-1.0..=1.0`, or `None` if the report doesn't contain the axis
+    pub axes: [Option<f32>; 6],
+    /// The direction of the hat switch in steps of 45° clockwise, starting with `0` for up
+    ///
+    /// `None` if the hat switch is centered or the report doesn't contain one.
+    pub hat: Option<u8>,
+    /// The pressed buttons, button `n` is stored in bit `n - 1`
+    ///
+    /// Buttons beyond button 128 are ignored.
+    pub buttons: u128
+}
+
+impl GamepadState {
+    /// Whether the button with the given number (starting at 1) is pressed
+    pub fn button(&self, number: u16) -> bool {
+        (1..=128).contains(&number) && self.buttons & (1 << (number - 1)) != 0
+    }
+}
+
+impl Device {
+    /// Reads the next input report and decodes the standard gamepad usages
+    ///
+    /// Usages that the report doesn't contain are left at their defaults, this happens for devices that split their state
+    /// across multiple reports. Returns an [ErrorSource::Unsupported](crate::ErrorSource::Unsupported) error if the device
+    /// is not a gamepad or joystick (see [DeviceInfo::is_gamepad](crate::DeviceInfo::is_gamepad))
+    /// or the report descriptor is not available on this platform (see [Device::report_descriptor]).
+    pub async fn read_gamepad(&self) -> HidResult<GamepadState> {
+        ensure!(self.info.is_gamepad(), HidError::unsupported());
+        let report = self.read_structured().await?;
+        let layout = self.cached_layout().await?;
+
+        let mut state = GamepadState::default();
+        for (axis, usage) in state.axes.iter_mut().zip(AXES) {
+            *axis = report
+                .value(usage)
+                .zip(find_field(layout, report.report_id, usage))
+                .map(|(value, field)| normalize(value, field));
+        }
+        state.hat = report
+            .value(HAT_SWITCH)
+            .zip(find_field(layout, report.report_id, HAT_SWITCH))
+            .and_then(|(value, field)| hat_direction(value, field));
+        let pressed = report
+            .values
+            .iter()
+            .filter(|(_, value)| **value != 0)
+            .map(|(usage, _)| usage)
+            .chain(&report.active)
+            .filter(|usage| *usage >> 16 == BUTTON_PAGE);
+        for usage in pressed {
+            let number = *usage & 0xffff;
+            if (1..=128).contains(&number) {
+                state.buttons |= 1 << (number - 1);
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// The variable input field of the given report that contains the usage
+fn find_field(layout: &ReportLayout, report_id: u8, usage: u32) -> Option<&ReportField> {
+    layout.fields.iter().find(|field| {
+        field.report_type == ReportType::Input && field.report_id == report_id && field.is_variable() && field.index_of(usage).is_some()
+    })
+}
+
+fn normalize(value: i32, field: &ReportField) -> f32 {
+    let (min, max) = (field.logical_minimum as f32, field.logical_maximum as f32);
+    match max > min {
+        true => ((value as f32 - min) / (max - min) * 2.0 - 1.0).clamp(-1.0, 1.0),
+        false => 0.0
+    }
+}
+
+/// Converts the hat switch value into 45° steps, values outside of the logical range mean that the hat is centered
+fn hat_direction(value: i32, field: &ReportField) -> Option<u8> {
+    if !(field.logical_minimum..=field.logical_maximum).contains(&value) {
+        return None;
+    }
+    let position = (value - field.logical_minimum) as u8;
+    // Hat switches either have 8 positions or only the 4 main directions
+    match field.logical_maximum - field.logical_minimum {
+        3 => Some(position * 2),
+        _ => Some(position % 8)
+    }
+}
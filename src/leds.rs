@@ -0,0 +1,45 @@
+//! Helpers for the indicator LEDs of keyboards (usage page `0x08`)
+
+use crate::{ensure, Device, HidError, HidResult, ReportType};
+
+/// LED: Num Lock
+const NUM_LOCK: u32 = 0x0008_0001;
+/// LED: Caps Lock
+const CAPS_LOCK: u32 = 0x0008_0002;
+/// LED: Scroll Lock
+const SCROLL_LOCK: u32 = 0x0008_0003;
+
+impl Device {
+    /// Turns the Caps Lock, Num Lock and Scroll Lock LEDs of a keyboard on or off
+    ///
+    /// The LEDs are located in the output report declared by the report descriptor.
+    /// All other values of that report (i.e the Compose and Kana LEDs) are cleared, as output reports can't be read back reliably.
+    /// Returns an [ErrorSource::Unsupported](crate::ErrorSource::Unsupported) error if the device doesn't declare any of the LEDs
+    /// or the report descriptor is not available on this platform (see [Device::report_descriptor]).
+    pub async fn set_leds(&self, caps: bool, num: bool, scroll: bool) -> HidResult<()> {
+        let layout = self.cached_layout().await?;
+        let report_id = [CAPS_LOCK, NUM_LOCK, SCROLL_LOCK]
+            .into_iter()
+            .find_map(|usage| layout.find(ReportType::Output, usage))
+            .map(|(field, _)| field.report_id)
+            .ok_or_else(|| HidError::unsupported())?;
+
+        let mut report = vec![0u8; layout.report_length(ReportType::Output, report_id) + 1];
+        report[0] = report_id;
+        for (usage, on) in [(CAPS_LOCK, caps), (NUM_LOCK, num), (SCROLL_LOCK, scroll)] {
+            // Keyboards may split their LEDs across multiple reports, only the ones in the located report are set
+            let Some((field, index)) = layout
+                .find(ReportType::Output, usage)
+                .filter(|(field, _)| field.report_id == report_id)
+            else {
+                continue;
+            };
+            let value = match on {
+                true => field.logical_maximum,
+                false => field.logical_minimum
+            };
+            ensure!(field.insert(&mut report[1..], index, value), HidError::custom("Output report is too short"));
+        }
+        self.write_output_report(&report).await
+    }
+}
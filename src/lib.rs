@@ -1,16 +1,79 @@
 #![doc = include_str!("../README.md")]
 
+mod aggregate;
 mod backend;
+mod battery;
+mod cache;
+mod callback;
+mod criteria;
+mod descriptor;
 mod error;
+mod gamepad;
+mod leds;
+mod pool;
+mod report;
+mod retry;
+mod router;
+mod sensors;
+mod sink;
+mod stream;
+mod structured;
+mod timer;
+mod token;
+mod usages;
+mod values;
+mod watchdog;
+mod weak;
 
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::fmt::{Debug, Formatter};
-use std::future::Future;
+use std::future::{poll_fn, Future};
 use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use futures_core::Stream;
 use static_assertions::assert_impl_all;
 use crate::backend::{BackendDevice, BackendDeviceId, BackendPrivateData};
-pub use crate::error::{ErrorSource, HidError, HidResult};
+pub use crate::aggregate::AggregateReader;
+pub use crate::cache::clear_descriptor_cache;
+pub use crate::criteria::{DeviceCriteria, DeviceCriteriaBuilder};
+pub use crate::descriptor::{ReportField, ReportIds, ReportLayout, ReportType};
+pub use crate::error::{set_error_hook, ErrorSource, HidError, HidResult};
+pub use crate::gamepad::GamepadState;
+pub use crate::pool::{ReportBuf, ReportPool};
+pub use crate::report::{Report, ReportId};
+pub use crate::retry::RetryPolicy;
+pub use crate::router::{ReportIdStream, ReportRouter};
+pub use crate::sink::ReportSink;
+pub use crate::stream::{merge_readers, select_reports, DedupInputReportStream, InputReportStream, MergedReports, PooledInputReportStream, SelectReports};
+pub use crate::structured::StructuredReport;
+pub use crate::token::WriteToken;
+pub use crate::usages::{set_usage_names, usage_name, usage_page_name, UsageNames};
+pub use crate::watchdog::Watchdog;
+pub use crate::weak::WeakDevice;
+#[cfg(target_os = "linux")]
+pub use crate::backend::{VirtualDevice, VirtualDeviceInfo};
+
+/// The name of the platform backend that this library was compiled with (i.e `hidraw`, `win32`, `winrt` or `iohidmanager`)
+///
+/// Exactly one backend is active per target, so the returned value never changes at runtime.
+pub const fn backend_name() -> &'static str {
+    backend::name()
+}
+
+/// Releases all global state of this library
+///
+/// This includes the descriptor cache, the names registered with [set_usage_names] and the [error hook](set_error_hook).
+/// It is meant for a clean shutdown and for test harnesses that check for leaks; the library stays usable afterwards.
+/// The per-thread IOKit run loop on macOS isn't affected, it is already released together with the last open [Device].
+pub fn shutdown() {
+    cache::clear_descriptor_cache();
+    usages::clear_usage_names();
+    error::clear_error_hook();
+}
 
 /// A struct containing basic information about a device
 ///
@@ -30,6 +93,7 @@ pub struct DeviceInfo {
     /// The HID usage page
     pub usage_page: u16,
 
+    pub(crate) collections: Arc<[(u16, u16)]>,
     pub(crate) private_data: BackendPrivateData,
 }
 
@@ -38,24 +102,338 @@ impl DeviceInfo {
     ///
     /// If this library fails to retrieve the [DeviceInfo] of a device it will be automatically excluded.
     /// Register a `log` compatible logger at `trace` level for more information about the discarded devices.
+    ///
+    /// The information of each device is retrieved lazily while the stream is polled,
+    /// so dropping the future or the stream early cancels the remaining work.
     pub fn enumerate() -> impl Future<Output = HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send>> {
         backend::enumerate()
     }
 
+    /// Enumerates all **accessible** HID devices and retries to retrieve the [DeviceInfo] of a device according to the given [RetryPolicy]
+    ///
+    /// Right after a device got plugged in, the OS may fail to provide its properties for a short time.
+    /// Retrying prevents such devices from being excluded, at the cost of a slower enumeration if a device keeps failing.
+    ///
+    /// Retries are currently only performed on Windows (Win32) and macOS, the other backends behave like [DeviceInfo::enumerate].
+    pub fn enumerate_with_retry(policy: RetryPolicy) -> impl Future<Output = HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send>> {
+        backend::enumerate_with_retry(policy)
+    }
+
+    /// Enumerates all **accessible** HID devices using only the information that is cached by the OS
+    ///
+    /// Requesting information like string descriptors from a device resumes it if it is suspended to save power.
+    /// This avoids such requests, at the cost of some optional information (i.e [SerialNumberExt::serial_number]) being `None`.
+    /// Devices whose information is not cached at all are excluded.
+    ///
+    /// - Linux: only reads sysfs and never opens the device nodes
+    /// - Windows (Win32): skips the serial number and manufacturer, the product name is still requested from the device
+    /// - Windows (WinRT): behaves like [DeviceInfo::enumerate], as the information is only available from opened devices
+    /// - macOS: behaves like [DeviceInfo::enumerate], as all information is read from the I/O Registry
+    pub fn enumerate_passive() -> impl Future<Output = HidResult<impl Stream<Item = DeviceInfo> + Unpin + Send>> {
+        backend::enumerate_passive()
+    }
+
+    /// Counts the HID devices without retrieving their information
+    ///
+    /// This counts the devices as reported by the OS (hidraw nodes, device interfaces or IOHIDDevices),
+    /// so unlike [DeviceInfo::enumerate] devices with multiple top-level collections are counted once on Linux and macOS,
+    /// and devices that are not accessible are included.
+    pub fn count() -> impl Future<Output = HidResult<usize>> {
+        backend::count()
+    }
+
     /// Opens the associated device in the requested [AccessMode]
+    ///
+    /// On macOS opening devices that capture user input (i.e keyboards) fails with [ErrorSource::PermissionDenied]
+    /// unless the application was granted the Input Monitoring permission.
     pub async fn open(&self, mode: AccessMode) -> HidResult<Device> {
-        let dev = backend::open(&self.id.0, mode).await?;
-        Ok(Device {
+        self.open_with_options(&OpenOptions::new().mode(mode)).await
+    }
+
+    /// Opens the associated device with as much access as the OS grants
+    ///
+    /// This first tries [AccessMode::ReadWrite] and falls back to [AccessMode::Read] and then [AccessMode::Write],
+    /// so a missing write permission doesn't prevent reading. Use [Device::mode] to check which access was granted.
+    /// If all attempts fail the error of the first attempt is returned.
+    pub async fn open_best_effort(&self) -> HidResult<Device> {
+        let error = match self.open(AccessMode::ReadWrite).await {
+            Ok(device) => return Ok(device),
+            Err(e) => e
+        };
+        log::trace!("Failed to open {:?} for reading and writing, falling back to a single direction\n\tbecause {error:?}", self.name);
+        for mode in [AccessMode::Read, AccessMode::Write] {
+            match self.open(mode).await {
+                Ok(device) => return Ok(device),
+                Err(e) => log::trace!("Failed to open {:?} in {:?} mode\n\tbecause {e:?}", self.name, mode)
+            }
+        }
+        Err(error)
+    }
+
+    /// Opens the associated device with the given [OpenOptions]
+    pub async fn open_with_options(&self, options: &OpenOptions) -> HidResult<Device> {
+        self.open_device(options, Vec::new()).await
+    }
+
+    /// Opens the associated device and sends the given [InitReport]s before returning it
+    ///
+    /// Many devices only start streaming input reports after receiving some kind of initialization report.
+    /// If sending any of the reports fails the device is closed again and the error is returned.
+    /// The reports are sent again whenever the device gets reopened by [Device::reconnect].
+    pub async fn open_with_init(&self, mode: AccessMode, init_reports: impl IntoIterator<Item = InitReport>) -> HidResult<Device> {
+        self.open_device(&OpenOptions::new().mode(mode), init_reports.into_iter().collect())
+            .await
+    }
+
+    async fn open_device(&self, options: &OpenOptions, init_reports: Vec<InitReport>) -> HidResult<Device> {
+        let dev = options
+            .busy_retry
+            .unwrap_or(RetryPolicy::NONE)
+            .run_while(|| backend::open(&self.id.0, options.mode), |e| matches!(e.error_source(), ErrorSource::Busy))
+            .await?;
+        let strip_report_id = match options.strip_report_id {
+            true => !self.cached_report_ids(&dev).await?.input.is_empty(),
+            false => false
+        };
+        let mut device = Device {
             inner: dev,
             info: self.clone(),
-            mode
-        })
+            options: options.clone(),
+            strip_report_id,
+            init_reports,
+            usage_filter: None,
+            skipped_reports: SkippedReports::default(),
+            report_ids: OnceLock::new(),
+            layout: OnceLock::new()
+        };
+        if options.filter_by_usage {
+            device.usage_filter = device.usage_report_ids().await?;
+        }
+        if options.strict_report_length {
+            device.cached_layout().await?;
+        }
+        for report in options.init_reports.iter().chain(&device.init_reports) {
+            match report {
+                InitReport::Output(data) => device.write_output_report(data).await?,
+                InitReport::Feature(data) => device.send_feature_report(data).await?
+            }
+        }
+        Ok(device)
+    }
+
+    /// The report ids of the opened device, which are shared by all opened instances of this device (see [clear_descriptor_cache])
+    async fn cached_report_ids(&self, dev: &BackendDevice) -> HidResult<ReportIds> {
+        let key = cache::key(self);
+        if let Some(report_ids) = cache::report_ids(&key) {
+            return Ok(report_ids);
+        }
+        let report_ids = dev.report_ids().await?;
+        cache::insert_report_ids(key, report_ids.clone());
+        Ok(report_ids)
+    }
+
+    /// The number of top-level collections of the physical device
+    ///
+    /// Every top-level collection is reported as a separate [DeviceInfo], so this is the number of [DeviceInfo]s that share the same physical device.
+    /// This is determined during enumeration without fully parsing the report descriptor.
+    /// On Windows every top-level collection is exposed as an independent device, so this is always `1`.
+    pub fn collection_count(&self) -> usize {
+        self.collections.len()
+    }
+
+    /// The usage pages and usage ids (`(usage_page, usage_id)`) of all top-level collections of the physical device
+    ///
+    /// Every entry corresponds to one of the [DeviceInfo]s that share the same physical device, including this one.
+    /// On Windows every top-level collection is exposed as an independent device, so this only contains the usage of this device.
+    pub fn collections(&self) -> &[(u16, u16)] {
+        &self.collections
     }
 
     /// Convenience method for easily finding a specific device
     pub fn matches(&self, usage_page: u16, usage_id: u16, vendor_id: u16, product_id: u16) -> bool {
         self.usage_page == usage_page && self.usage_id == usage_id && self.vendor_id == vendor_id && self.product_id == product_id
     }
+
+    /// Checks if any top-level collection of the physical device is Generic Desktop / Keyboard
+    ///
+    /// This checks all [DeviceInfo::collections], so every [DeviceInfo] of a composite device with a keyboard collection matches,
+    /// even if its own usage is a different one (i.e Consumer Control). Compare [DeviceInfo::usage_id] to only match the keyboard collection itself.
+    pub fn is_keyboard(&self) -> bool {
+        self.has_collection(GENERIC_DESKTOP, &[0x06])
+    }
+
+    /// Checks if any top-level collection of the physical device is Generic Desktop / Mouse
+    ///
+    /// Like [DeviceInfo::is_keyboard] this checks all [DeviceInfo::collections].
+    pub fn is_mouse(&self) -> bool {
+        self.has_collection(GENERIC_DESKTOP, &[0x02])
+    }
+
+    /// Checks if any top-level collection of the physical device is Generic Desktop / Game Pad or Generic Desktop / Joystick
+    ///
+    /// Like [DeviceInfo::is_keyboard] this checks all [DeviceInfo::collections].
+    pub fn is_gamepad(&self) -> bool {
+        self.has_collection(GENERIC_DESKTOP, &[0x04, 0x05])
+    }
+
+    fn has_collection(&self, usage_page: u16, usage_ids: &[u16]) -> bool {
+        self.collections().iter().any(|&(page, id)| page == usage_page && usage_ids.contains(&id))
+    }
+
+    /// A human readable description of the usage, i.e `Usage Page: 0x01 (Generic Desktop), Usage: 0x02 (Mouse)`
+    ///
+    /// The names are omitted if they are unknown (see [usage_page_name] and [usage_name]).
+    pub fn usage_description(&self) -> String {
+        let mut description = format!("Usage Page: {:#04x}", self.usage_page);
+        if let Some(name) = usage_page_name(self.usage_page) {
+            description += &format!(" ({name})");
+        }
+        description += &format!(", Usage: {:#04x}", self.usage_id);
+        if let Some(name) = usage_name(((self.usage_page as u32) << 16) | self.usage_id as u32) {
+            description += &format!(" ({name})");
+        }
+        description
+    }
+
+    /// Checks if the device belongs to the given vendor and has one of the given product ids
+    pub fn in_family(&self, vendor_id: u16, product_ids: &[u16]) -> bool {
+        self.vendor_id == vendor_id && product_ids.contains(&self.product_id)
+    }
+
+    /// The path of the device in the USB topology, starting at the host controller
+    ///
+    /// Every component is one level deeper in the topology, so all devices below a hub share the path of the hub as prefix.
+    /// On Linux this is the sysfs path of the device (i.e `/sys/devices/pci0000:00/0000:00:14.0/usb1/1-2/1-2.3/...`),
+    /// on macOS the bus and port numbers encoded in the location id (i.e `14/3/2`).
+    /// Returns `None` on Windows and for devices whose position is unknown.
+    pub fn topology_path(&self) -> Option<String> {
+        self.topology()
+    }
+
+    /// The bus the device is connected through
+    ///
+    /// On Windows only Bluetooth devices are recognized (see `WindowsDeviceInfoExt::is_bluetooth`), all other devices are [BusType::Unknown].
+    pub fn bus(&self) -> BusType {
+        self.connected_bus()
+    }
+
+    /// The `bInterfaceSubClass` of the usb interface that the device belongs to (i.e `0x01` for boot interfaces)
+    ///
+    /// This is only available for usb devices on Linux and macOS, otherwise `None` is returned.
+    pub fn interface_subclass(&self) -> Option<u8> {
+        self.interface_class().map(|(subclass, _)| subclass)
+    }
+
+    /// The `bInterfaceProtocol` of the usb interface that the device belongs to (i.e `0x01` for boot keyboards and `0x02` for boot mice)
+    ///
+    /// The protocol is only meaningful for boot interfaces (see [DeviceInfo::interface_subclass]).
+    /// This is only available for usb devices on Linux and macOS, otherwise `None` is returned.
+    pub fn interface_protocol(&self) -> Option<u8> {
+        self.interface_class().map(|(_, protocol)| protocol)
+    }
+
+    /// Checks if the device is connected below the given [topology path](DeviceInfo::topology_path)
+    ///
+    /// The path is compared component by component, so `/sys/devices/.../usb1/1-2` matches `/sys/devices/.../usb1/1-2/1-2.3`, but not `/sys/devices/.../usb1/1-20`.
+    /// Always returns `false` on Windows, where the topology path isn't available.
+    pub fn is_below(&self, prefix: &str) -> bool {
+        let prefix = prefix.trim_end_matches('/');
+        self.topology().is_some_and(|path| {
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    /// A hash of the identity and the physical location of the device that stays the same when the device is reconnected to the same port
+    ///
+    /// The fingerprint covers the vendor id, product id, usage, manufacturer, serial number and the platform specific location of the device
+    /// (the physical path on Linux, the interface path on Windows and the location id on macOS).
+    /// Identical devices without a serial number that are connected to the same port one after another produce the same fingerprint.
+    /// The value is stable across runs of the program, but not necessarily across platforms or versions of this crate.
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = Fnv1a::default();
+        self.vendor_id.hash(&mut hasher);
+        self.product_id.hash(&mut hasher);
+        self.usage_page.hash(&mut hasher);
+        self.usage_id.hash(&mut hasher);
+        self.manufacturer().hash(&mut hasher);
+        self.serial_number().hash(&mut hasher);
+        self.physical_location().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Reads the string descriptor with the given index, which some devices use to store additional metadata
+    ///
+    /// Returns `None` if the string is empty or doesn't exist. Index 0 is the table of supported languages and always returns `None`.
+    /// On Linux this is only supported for usb devices and requires write access to the usbfs node of the device.
+    /// On Windows failed requests are treated as missing strings. This is currently not supported on macOS.
+    pub fn string_descriptor(&self, index: u8) -> HidResult<Option<String>> {
+        match index {
+            0 => Ok(None),
+            index => backend::string_descriptor(&self.id.0, index)
+        }
+    }
+
+    /// Looks up the currently connected device that this info describes, preferring a device with the same id
+    ///
+    /// Returns an [ErrorSource::NotFound] error if the device is not connected.
+    async fn rediscover(&self) -> HidResult<DeviceInfo> {
+        let mut devices = DeviceInfo::enumerate().await?;
+        let mut candidate = None;
+        while let Some(info) = poll_fn(|cx| Pin::new(&mut devices).poll_next(cx)).await {
+            if info.id == self.id {
+                return Ok(info);
+            }
+            if candidate.is_none() && self.is_same_device(&info) {
+                candidate = Some(info);
+            }
+        }
+        candidate.ok_or_else(|| HidError::not_found())
+    }
+
+    /// Checks if both infos describe the same function of the same physical device, ignoring the OS specific id
+    fn is_same_device(&self, other: &DeviceInfo) -> bool {
+        self.vendor_id == other.vendor_id
+            && self.product_id == other.product_id
+            && self.usage_page == other.usage_page
+            && self.usage_id == other.usage_id
+            && self.serial_number() == other.serial_number()
+    }
+
+    /// Checks if the device is already in use by another process or driver (best-effort)
+    ///
+    /// This helps diagnosing devices that open successfully but never produce any reports because the OS consumes them.
+    /// - Linux: the device is bound to an input driver or opened by another visible process
+    /// - Windows (Win32): the device can't be opened for read/write access
+    /// - macOS: another client seized the device
+    /// - Windows (WinRT): always returns `false`
+    pub fn is_claimed(&self) -> bool {
+        backend::is_claimed(&self.id.0)
+    }
+}
+
+impl DeviceInfo {
+    /// Enumerates all devices and returns the first one that matches the given [DeviceCriteria]
+    ///
+    /// The enumeration stops at the first match, so the information of the remaining devices is never retrieved.
+    pub async fn find_first(criteria: &DeviceCriteria) -> HidResult<Option<DeviceInfo>> {
+        let mut devices = Self::enumerate().await?;
+        while let Some(info) = poll_fn(|cx| Pin::new(&mut devices).poll_next(cx)).await {
+            if criteria.matches(&info) {
+                return Ok(Some(info));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Like [DeviceInfo::find_first], but gives up once the enumeration takes longer than the given duration
+    ///
+    /// Fails with [ErrorSource::Timeout] if neither a match was found nor all devices were enumerated in time.
+    pub async fn find_first_timeout(criteria: &DeviceCriteria, timeout: Duration) -> HidResult<Option<DeviceInfo>> {
+        timer::timeout_at(Instant::now() + timeout, Self::find_first(criteria))
+            .await
+            .ok_or_else(|| HidError::timeout())?
+    }
 }
 
 impl Hash for DeviceInfo {
@@ -82,30 +460,612 @@ impl PartialEq for DeviceInfo {
 
 impl Eq for DeviceInfo {}
 
+/// Devices are ordered by their identifying properties (vendor id, product id, usage and serial number) first,
+/// so the same kind of device stays in the same place when sorted. The OS specific id only acts as a tiebreaker.
+impl Ord for DeviceInfo {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.vendor_id
+            .cmp(&other.vendor_id)
+            .then_with(|| self.product_id.cmp(&other.product_id))
+            .then_with(|| self.usage_page.cmp(&other.usage_page))
+            .then_with(|| self.usage_id.cmp(&other.usage_id))
+            .then_with(|| self.serial_number().cmp(&other.serial_number()))
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for DeviceInfo {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The Generic Desktop usage page
+const GENERIC_DESKTOP: u16 = 0x01;
+
+/// The buffer size for reading reports of devices whose report descriptor isn't known
+///
+/// Matches `HID_MAX_BUFFER_SIZE` of recent Linux kernels, which is the largest report that hidraw can deliver.
+const MAX_REPORT_SIZE: usize = 16384;
+
+/// The interval in which [Device::disconnected] checks the connection state on platforms without a removal signal
+#[cfg(any(all(target_os = "windows", feature = "win32"), target_os = "macos"))]
+const DISCONNECT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The 64 bit FNV-1a hash, which unlike the [DefaultHasher](std::hash::DefaultHasher) is guaranteed to never change
+struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv1a {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ *byte as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
 pub trait SerialNumberExt {
     fn serial_number(&self) -> Option<&str>;
 }
 
+pub trait ManufacturerExt {
+    /// The manufacturer string of the device, if it provides one
+    ///
+    /// On Linux this is only available for usb devices.
+    fn manufacturer(&self) -> Option<&str>;
+}
+
+/// Additional information that is only available for hidraw devices
+#[cfg(target_os = "linux")]
+pub trait HidrawDeviceInfoExt {
+    /// The bus type as reported by the kernel (i.e `BUS_USB` = 0x03, `BUS_BLUETOOTH` = 0x05)
+    fn bus_type(&self) -> u16;
+    /// The physical path of the device (i.e `usb-0000:00:14.0-1/input0`)
+    fn physical_path(&self) -> Option<&str>;
+}
+
+/// Additional information that is only available on Windows
+#[cfg(target_os = "windows")]
+pub trait WindowsDeviceInfoExt {
+    /// The device interface path (i.e `\\?\HID#VID_046D&PID_C52B&MI_02#...`), which is also the OS specific part of the [DeviceId]
+    ///
+    /// The path contains the serial number of the device or, for devices without one, an instance id that is derived from the port.
+    /// It therefore stays the same when the device is reconnected to the same port and can be persisted to find the device again.
+    fn interface_path(&self) -> String;
+
+    /// Whether the device is connected over Bluetooth, either Bluetooth classic or Bluetooth LE
+    ///
+    /// Bluetooth HID devices are enumerated like any other HID device, their interface path starts with the
+    /// Bluetooth HID service class (i.e `\\?\HID#{00001124-0000-1000-8000-00805f9b34fb}_VID&0002046d_PID&b01a#...`).
+    fn is_bluetooth(&self) -> bool {
+        const BLUETOOTH_SERVICES: [&str; 2] = ["{00001124-0000-1000-8000-00805f9b34fb}", "{00001812-0000-1000-8000-00805f9b34fb}"];
+        let path = self.interface_path().to_ascii_lowercase();
+        BLUETOOTH_SERVICES.iter().any(|service| path.contains(service))
+    }
+}
+
+/// Access to the underlying IOKit objects of an opened device on macOS
+#[cfg(target_os = "macos")]
+pub trait MacDeviceExt {
+    /// The `IOHIDDeviceRef` of the opened device, for calling IOKit functions that this crate doesn't wrap
+    ///
+    /// The reference is still owned by the [Device] and only valid as long as the device is alive.
+    /// It must not be released or closed, use `CFRetain` to keep the object itself alive for longer.
+    /// Scheduling the device on another run loop or registering another input report callback breaks reading.
+    fn io_hid_device(&self) -> io_kit_sys::hid::base::IOHIDDeviceRef;
+}
+
 /// A struct representing an opened device
 ///
 /// Dropping this struct will close the associated device
 pub struct Device {
     inner: BackendDevice,
     info: DeviceInfo,
-    mode: AccessMode
+    options: OpenOptions,
+    strip_report_id: bool,
+    init_reports: Vec<InitReport>,
+    usage_filter: Option<BTreeSet<u8>>,
+    skipped_reports: SkippedReports,
+    report_ids: OnceLock<Option<ReportIds>>,
+    layout: OnceLock<Arc<ReportLayout>>
 }
 
 impl Device {
+    /// Opens the first device that matches the given [DeviceCriteria] in [AccessMode::ReadWrite]
+    ///
+    /// Returns an [ErrorSource::NotFound] error if no device matches.
+    pub async fn open_first(criteria: &DeviceCriteria) -> HidResult<Device> {
+        match DeviceInfo::find_first(criteria).await? {
+            Some(info) => info.open(AccessMode::ReadWrite).await,
+            None => Err(HidError::not_found())
+        }
+    }
+
+    /// Opens the first device with the given vendor and product id in [AccessMode::ReadWrite]
+    ///
+    /// This is a shorthand for [Device::open_first].
+    pub async fn open_first_matching(vendor_id: u16, product_id: u16) -> HidResult<Device> {
+        let criteria = DeviceCriteria::builder()
+            .vendor_id(vendor_id)
+            .product_id(product_id)
+            .build();
+        Self::open_first(&criteria).await
+    }
+
     /// Read a input report from this device
-    pub fn read_input_report<'a>(&'a self, buf: &'a mut [u8]) -> impl Future<Output = HidResult<usize>> + Send + 'a {
-        debug_assert!(self.mode.readable());
-        self.inner.read_input_report(buf)
+    ///
+    /// For devices that use numbered reports the first byte contains the report id, unless [OpenOptions::strip_report_id] is set.
+    /// Some devices violate the HID specification by mixing numbered reports with an unnumbered one. The unnumbered report
+    /// is delivered with report id `0x0` in that case, use the report descriptor (see [Device::report_layout]) to interpret it.
+    /// The WinRT backend can't detect such devices and removes the report id `0x0`.
+    pub async fn read_input_report(&self, buf: &mut [u8]) -> HidResult<usize> {
+        let size = self.read_input_report_raw(buf).await?;
+        Ok(self.strip_report_id(buf, size))
+    }
+
+    /// Read a input report from this device and fail with [ErrorSource::ShortReport] if it is shorter than `min_len` bytes
+    ///
+    /// The length is checked after [OpenOptions::strip_report_id] was applied. The short report is still written into `buf`.
+    /// This allows indexing into the first `min_len` bytes without checking the length of every report.
+    pub async fn read_input_report_min(&self, buf: &mut [u8], min_len: usize) -> HidResult<usize> {
+        let size = self.read_input_report(buf).await?;
+        ensure!(size >= min_len, HidError::short_report(size, min_len));
+        Ok(size)
+    }
+
+    /// Read a input report from this device as a [Report] that keeps track of the report id
+    ///
+    /// Reports of devices that don't use numbered reports get the report id `0x0`.
+    /// Whether a device uses numbered reports is determined by [Device::report_ids],
+    /// on platforms that don't support it the first byte is always treated as report id.
+    /// [OpenOptions::strip_report_id] has no effect on this function.
+    pub async fn read_report(&self) -> HidResult<Report> {
+        let numbered = self
+            .cached_report_ids()
+            .await
+            .is_none_or(|ids| !ids.input.is_empty());
+        let mut buf = vec![0u8; self.load_input_buffer_size().await + 1];
+        // Unnumbered reports are read behind the zeroed first byte, which becomes their report id
+        let size = match numbered {
+            true => self.read_input_report_raw(&mut buf).await?,
+            false => self.read_input_report_raw(&mut buf[1..]).await? + 1
+        };
+        buf.truncate(size);
+        Ok(Report::from_full_bytes(buf))
+    }
+
+    /// Waits for the first input report after opening the device, i.e a status report that signals that the device is ready
+    ///
+    /// Behaves like [Device::read_report], but fails with [ErrorSource::Timeout] if no report arrived within the given duration.
+    /// Reports that arrived before this is called are returned as well, so call it right after opening the device
+    /// (or use [OpenOptions::init_report] to send the reports that make the device ready).
+    pub async fn wait_for_first_report(&self, timeout: Duration) -> HidResult<Report> {
+        timer::timeout_at(Instant::now() + timeout, self.read_report())
+            .await
+            .unwrap_or_else(|| Err(HidError::timeout()))
+    }
+
+    /// Reads the next input report that passes the usage filter without removing the report id
+    async fn read_input_report_raw(&self, buf: &mut [u8]) -> HidResult<usize> {
+        debug_assert!(self.options.mode.readable());
+        loop {
+            let size = self.inner.read_input_report(buf).await?;
+            if self.accepts_report(&buf[..size]) {
+                self.check_report_length(&buf[..size])?;
+                return Ok(size);
+            }
+        }
+    }
+
+    /// Read a input report from this device without waiting
+    ///
+    /// Returns `None` if no input report is currently available.
+    /// Under Linux the hidraw node is always opened in nonblocking mode, so this performs a single direct read
+    /// while [Device::read_input_report] waits for the node to become readable and retries.
+    /// On other platforms this checks if the backend has already received a report.
+    pub fn try_read_input_report(&self, buf: &mut [u8]) -> HidResult<Option<usize>> {
+        debug_assert!(self.options.mode.readable());
+        while let Some(size) = self.inner.try_read_input_report(buf)? {
+            if self.accepts_report(&buf[..size]) {
+                self.check_report_length(&buf[..size])?;
+                return Ok(Some(self.strip_report_id(buf, size)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Discards all input reports that the OS already buffered for this device and returns how many were discarded
+    ///
+    /// The reports that [Device::read_input_report_for_id] buffered (see [UnmatchedReports::Buffer]) are discarded as well.
+    /// This is meant to be called after switching the mode of the device (i.e. with a feature report),
+    /// so that reports in the old format aren't mistaken for reports in the new one.
+    /// The device itself keeps no reassembly state, but stream adapters like [InputReportStream::dedup] do and have to be recreated.
+    pub fn reset(&self) -> HidResult<usize> {
+        debug_assert!(self.options.mode.readable());
+        let mut buf = vec![0u8; self.input_buffer_size()];
+        let mut discarded = self.skipped_reports.clear();
+        while self.inner.try_read_input_report(&mut buf)?.is_some() {
+            discarded += 1;
+        }
+        log::trace!("Discarded {discarded} buffered input reports of {:?}", self.info.name);
+        Ok(discarded)
+    }
+
+    /// Resolves once this device got disconnected
+    ///
+    /// This allows to stop other work (i.e with `select!`) as soon as the device is removed, without waiting for a read to fail.
+    /// No input reports are consumed while waiting.
+    ///
+    /// - Linux: waits for the hidraw node to signal `POLLHUP`
+    /// - macOS: waits for the removal callback of the device, write-only devices poll the I/O Registry every quarter second instead
+    /// - Windows (Win32): polls the device every quarter second, so the future resolves up to a quarter second after the removal
+    /// - Windows (WinRT): fails immediately with [ErrorSource::Unsupported], as the removal of an opened device can't be detected
+    pub fn disconnected(&self) -> impl Future<Output = HidResult<()>> + Send + '_ {
+        self.inner.disconnected()
+    }
+
+    /// Statistics about the input reports of this device since it was opened
+    pub fn stats(&self) -> ReadStats {
+        ReadStats {
+            dropped_reports: self.inner.dropped_reports()
+        }
+    }
+
+    /// Read a input report from this device or return `None` if no report arrived within the given duration
+    ///
+    /// The read is cancelled once the timeout expires, so the next call starts a new one.
+    /// Use [InputReportStream::next_timeout] to keep waiting for the same report across calls instead.
+    pub fn read_input_report_timeout<'a>(&'a self, buf: &'a mut [u8], timeout: Duration) -> impl Future<Output = HidResult<Option<usize>>> + Send + 'a {
+        self.read_input_report_deadline(buf, Instant::now() + timeout)
+    }
+
+    /// Read a input report from this device or return `None` if no report arrived before the given deadline
+    ///
+    /// This is useful for loops that issue several operations against the same absolute deadline.
+    pub fn read_input_report_deadline<'a>(&'a self, buf: &'a mut [u8], deadline: Instant) -> impl Future<Output = HidResult<Option<usize>>> + Send + 'a {
+        debug_assert!(self.options.mode.readable());
+        async move { timer::timeout_at(deadline, self.read_input_report(buf)).await.transpose() }
+    }
+
+    /// Read the next input report with the given report id from this device
+    ///
+    /// Input reports with other ids that arrive in the meantime are handled according to [OpenOptions::unmatched_reports]:
+    /// by default they are **discarded** and not returned by later reads.
+    /// This is only meaningful for devices that use numbered reports, as the first byte of every report is compared with `report_id`.
+    /// Reports are read like with [Device::read_input_report], so [OpenOptions::filter_by_usage] and [OpenOptions::strict_report_length] apply.
+    pub async fn read_input_report_for_id(&self, report_id: impl Into<ReportId>, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        let ReportId(report_id) = report_id.into();
+        let size = self
+            .skipped_reports
+            .read_for_id(self.options.unmatched_reports, report_id, buf, |mut buf| async move {
+                let result = self.read_input_report_raw(&mut buf).await;
+                (buf, result)
+            })
+            .await?;
+        Ok(self.strip_report_id(buf, size))
+    }
+
+    /// Checks if the input report belongs to the usage of this device (see [OpenOptions::filter_by_usage])
+    fn accepts_report(&self, report: &[u8]) -> bool {
+        match (&self.usage_filter, report.first()) {
+            (Some(report_ids), Some(id)) if !report_ids.contains(id) => {
+                log::trace!("Discarding input report with id {id} that belongs to another usage");
+                false
+            }
+            _ => true
+        }
+    }
+
+    fn check_report_length(&self, report: &[u8]) -> HidResult<()> {
+        // The layout is always loaded when opening a device in strict mode
+        let Some(layout) = self.layout.get().filter(|_| self.options.strict_report_length) else {
+            return Ok(());
+        };
+        let (report_id, actual) = match (layout.is_numbered(ReportType::Input), report.first()) {
+            (true, Some(&id)) => (id, report.len() - 1),
+            _ => (0x0, report.len())
+        };
+        let expected = layout.report_length(ReportType::Input, report_id);
+        ensure!(actual == expected, HidError::length_mismatch(report_id, expected, actual));
+        Ok(())
+    }
+
+    fn strip_report_id(&self, buf: &mut [u8], size: usize) -> usize {
+        match self.strip_report_id {
+            true => remove_report_id(buf, size),
+            false => size
+        }
+    }
+
+    /// Read a input report from this device into a newly allocated [Bytes](bytes::Bytes) buffer of at most `max_len` bytes
+    ///
+    /// Behaves like [Device::read_input_report], but avoids copying the report when handing it to code that works with the `bytes` crate.
+    #[cfg(feature = "bytes")]
+    pub async fn read_input_report_bytes(&self, max_len: usize) -> HidResult<bytes::Bytes> {
+        let mut buf = bytes::BytesMut::zeroed(max_len);
+        let size = self.read_input_report(&mut buf).await?;
+        buf.truncate(size);
+        Ok(buf.freeze())
     }
 
     /// Write an output report to this device
-    pub fn write_output_report<'a>(&'a self, buf: &'a [u8]) -> impl Future<Output = HidResult<()>> + Send + 'a {
-        debug_assert!(self.mode.writeable());
-        self.inner.write_output_report(buf)
+    ///
+    /// The first byte must contain the report id or `0x0` if the device does not use numbered reports.
+    /// In the latter case only the remaining bytes are sent to the device.
+    /// Report id `0x0` is reserved, so writing it to a device that uses numbered output reports fails.
+    ///
+    /// The report is sent with the [Transport] selected by [OpenOptions::output_transport].
+    ///
+    /// Retrying a write after its future got dropped (i.e by a timeout) may send the report twice,
+    /// use [Device::write_output_report_once] for devices that don't tolerate duplicated reports.
+    pub async fn write_output_report(&self, buf: &[u8]) -> HidResult<()> {
+        self.write_output_report_via(buf, self.options.output_transport).await
+    }
+
+    /// Write an output report to this device using the given [Transport] instead of the default one
+    ///
+    /// Fails with [ErrorSource::Unsupported] if the platform can't send output reports over the requested transport.
+    pub async fn write_output_report_via(&self, buf: &[u8], transport: Transport) -> HidResult<()> {
+        debug_assert!(self.options.mode.writeable());
+        if let Some(report_ids) = self.cached_report_ids().await {
+            report_ids.check_output_report(buf)?;
+        }
+        match transport {
+            Transport::Interrupt => self.inner.write_output_report(buf).await,
+            Transport::Control => self.inner.set_output_report(buf).await
+        }
+    }
+
+    /// Write an output report with the given report id to this device
+    ///
+    /// Use `0x0` as `report_id` for devices that do not use numbered reports.
+    pub async fn write_output_report_with_id(&self, report_id: impl Into<ReportId>, payload: &[u8]) -> HidResult<()> {
+        let mut buf = Vec::with_capacity(payload.len() + 1);
+        buf.push(report_id.into().0);
+        buf.extend_from_slice(payload);
+        self.write_output_report(&buf).await
+    }
+
+    /// Writes the output report and waits for the first input report that satisfies the predicate
+    ///
+    /// Input reports that don't match (i.e replies to other commands or periodic reports) are discarded.
+    /// Fails with [ErrorSource::Timeout] if no matching report arrived within the given duration.
+    pub async fn request_response(&self, out: &[u8], match_fn: impl Fn(&[u8]) -> bool, timeout: Duration) -> HidResult<Vec<u8>> {
+        let deadline = Instant::now() + timeout;
+        self.write_output_report(out).await?;
+        let mut buf = vec![0u8; self.load_input_buffer_size().await];
+        loop {
+            let size = self
+                .read_input_report_deadline(&mut buf, deadline)
+                .await?
+                .ok_or_else(|| HidError::timeout())?;
+            if match_fn(&buf[..size]) {
+                buf.truncate(size);
+                return Ok(buf);
+            }
+            log::trace!("Discarding input report that doesn't match the expected response");
+        }
+    }
+
+    /// Waits until all previously written output reports were delivered to the device
+    ///
+    /// This is only relevant if the future of a previous [Device::write_output_report] call was dropped before it completed,
+    /// as completing a write already implies that the report was delivered.
+    pub fn flush(&self) -> impl Future<Output = HidResult<()>> + Send + '_ {
+        debug_assert!(self.options.mode.writeable());
+        self.inner.flush()
+    }
+
+    /// Send a feature report to this device
+    ///
+    /// The first byte must contain the report id or `0x0` if the device does not use numbered reports
+    pub fn send_feature_report<'a>(&'a self, buf: &'a [u8]) -> impl Future<Output = HidResult<()>> + Send + 'a {
+        self.inner.send_feature_report(buf)
+    }
+
+    /// Read a feature report from this device
+    ///
+    /// The first byte of `buf` must contain the requested report id or `0x0` if the device does not use numbered reports.
+    /// The returned report starts with the report id as well.
+    pub fn get_feature_report<'a>(&'a self, buf: &'a mut [u8]) -> impl Future<Output = HidResult<usize>> + Send + 'a {
+        self.inner.get_feature_report(buf)
+    }
+
+    /// Reads back the current state of an output report from the device (`GET_REPORT` with the output report type)
+    ///
+    /// The returned report starts with the report id, which is `0x0` for devices that do not use numbered reports.
+    /// This is currently only supported on Linux 5.11 or newer and macOS, and many devices reject the request.
+    pub async fn get_output_report(&self, report_id: impl Into<ReportId>, buf: &mut [u8]) -> HidResult<usize> {
+        ensure!(!buf.is_empty(), HidError::zero_sized_data());
+        buf[0] = report_id.into().0;
+        self.inner.get_output_report(buf).await
+    }
+
+    /// Read a feature report from this device while requesting exactly `length` bytes (including the report id)
+    ///
+    /// Under Linux and macOS the size of the buffer determines the requested length (`wLength` of the `GET_REPORT` request),
+    /// which some devices with variable length feature reports mishandle. This allows using a larger buffer while requesting less.
+    /// On Windows the requested length is always the feature report length declared by the device.
+    pub fn get_feature_report_with_length<'a>(&'a self, buf: &'a mut [u8], length: usize) -> impl Future<Output = HidResult<usize>> + Send + 'a {
+        let length = length.min(buf.len());
+        self.inner.get_feature_report(&mut buf[..length])
+    }
+
+    /// Reads `total` bytes from a feature report that the device returns in pages of `page_len` bytes
+    ///
+    /// This is meant for devices that advance an internal offset after every read of the feature report (i.e for firmware readback).
+    /// The report id is stripped from every page, so the result only contains the concatenated payloads.
+    pub async fn read_feature_paged(&self, report_id: impl Into<ReportId>, page_len: usize, total: usize) -> HidResult<Vec<u8>> {
+        ensure!(page_len > 0, HidError::zero_sized_data());
+        let ReportId(report_id) = report_id.into();
+        let mut result = Vec::with_capacity(total);
+        let mut page = vec![0u8; page_len + 1];
+        while result.len() < total {
+            page.fill(0);
+            page[0] = report_id;
+            let size = self.get_feature_report(&mut page).await?;
+            ensure!(size > 1, HidError::custom("Device returned an empty feature report page"));
+            let remaining = total - result.len();
+            result.extend_from_slice(&page[1..size.min(remaining + 1)]);
+        }
+        Ok(result)
+    }
+
+    /// Retrieves the raw physical descriptor of this device
+    ///
+    /// The physical descriptor describes which part of the human body is intended to operate each control.
+    /// The returned bytes start with descriptor set 0 followed by all other descriptor sets.
+    /// Returns `None` if the device does not provide a physical descriptor.
+    ///
+    /// This is currently only supported on Windows (Win32).
+    pub fn physical_descriptor(&self) -> impl Future<Output = HidResult<Option<Vec<u8>>>> + Send + '_ {
+        self.inner.physical_descriptor()
+    }
+
+    /// Retrieves the report ids that this device declares for each report type
+    ///
+    /// This is currently not supported by the WinRT backend.
+    pub fn report_ids(&self) -> impl Future<Output = HidResult<ReportIds>> + Send + '_ {
+        self.inner.report_ids()
+    }
+
+    /// Retrieves the raw report descriptor of this device
+    ///
+    /// This is currently only supported on Linux and macOS.
+    pub fn report_descriptor(&self) -> impl Future<Output = HidResult<Vec<u8>>> + Send + '_ {
+        self.inner.report_descriptor()
+    }
+
+    /// Retrieves and parses the report descriptor of this device
+    ///
+    /// This is currently only supported on Linux and macOS.
+    pub async fn report_layout(&self) -> HidResult<ReportLayout> {
+        Ok(ReportLayout::parse(&self.report_descriptor().await?))
+    }
+
+    /// The ids of the input reports that belong to the usage of this device
+    ///
+    /// Returns `None` if the device doesn't need to be filtered, because it doesn't use numbered reports
+    /// or the backend already exposes every top-level collection as a separate device.
+    async fn usage_report_ids(&self) -> HidResult<Option<BTreeSet<u8>>> {
+        let layout = match self.cached_layout().await {
+            Ok(layout) => layout,
+            Err(e) if matches!(e.error_source(), ErrorSource::Unsupported) => return Ok(None),
+            Err(e) => return Err(e)
+        };
+        let usage = (self.info.usage_page as u32) << 16 | self.info.usage_id as u32;
+        let report_ids = layout.collection_report_ids(ReportType::Input, usage);
+        Ok((!report_ids.is_empty() && !report_ids.contains(&0)).then_some(report_ids))
+    }
+
+    /// The report ids of this device, which are only retrieved once per opened device
+    ///
+    /// Returns `None` if the report ids are not available on this platform.
+    async fn cached_report_ids(&self) -> Option<&ReportIds> {
+        if let Some(report_ids) = self.report_ids.get() {
+            return report_ids.as_ref();
+        }
+        let report_ids = self
+            .info
+            .cached_report_ids(&self.inner)
+            .await
+            .map_err(|e| log::trace!("Failed to retrieve the report ids of {:?}\n\tbecause {e:?}", self.info.name))
+            .ok();
+        self.report_ids.get_or_init(|| report_ids).as_ref()
+    }
+
+    /// The parsed report descriptor, which is shared by all opened instances of this device (see [clear_descriptor_cache])
+    pub(crate) async fn cached_layout(&self) -> HidResult<&ReportLayout> {
+        if let Some(layout) = self.layout.get() {
+            return Ok(layout);
+        }
+        let key = cache::key(&self.info);
+        let layout = match cache::layout(&key) {
+            Some(layout) => layout,
+            None => {
+                let layout = Arc::new(self.report_layout().await?);
+                cache::insert_layout(key, layout.clone());
+                layout
+            }
+        };
+        Ok(self.layout.get_or_init(|| layout))
+    }
+
+    /// Turns this device into a [Stream] of input reports that are read into buffers of the given size
+    pub fn into_input_reports(self, buffer_size: usize) -> InputReportStream {
+        InputReportStream::new(self, buffer_size)
+    }
+
+    /// Turns this device into a [Stream] of input reports that skips reports which are identical to the previous report
+    ///
+    /// Shorthand for `into_input_reports(..).dedup()` with a buffer that fits every report, see [InputReportStream::dedup].
+    pub fn input_reports_dedup(self) -> DedupInputReportStream {
+        let buffer_size = self.input_buffer_size();
+        self.into_input_reports(buffer_size).dedup()
+    }
+
+    /// A buffer size that fits every input report of this device
+    ///
+    /// The size is derived from the report descriptor if it was already loaded, otherwise [MAX_REPORT_SIZE] is used.
+    pub(crate) fn input_buffer_size(&self) -> usize {
+        // One additional byte for the report id `0x0` that some platforms prepend to unnumbered reports
+        self.layout
+            .get()
+            .map(|layout| layout.max_report_length(ReportType::Input) + 1)
+            .filter(|size| *size > 1)
+            .unwrap_or(MAX_REPORT_SIZE)
+    }
+
+    /// Like [Device::input_buffer_size], but loads the report descriptor first
+    async fn load_input_buffer_size(&self) -> usize {
+        if let Err(e) = self.cached_layout().await {
+            log::trace!("Falling back to the maximum report size for {:?}\n\tbecause {e:?}", self.info.name);
+        }
+        self.input_buffer_size()
+    }
+
+    /// Wraps this device in a [Watchdog] that fails reads once the device stays silent for longer than `max_silence`
+    pub fn with_watchdog(self, max_silence: Duration) -> Watchdog {
+        Watchdog::new(self, max_silence)
+    }
+
+    /// Reopens this device after it got disconnected
+    ///
+    /// The device is looked up again by its id or, as the id may change when the device gets plugged in again,
+    /// by its vendor id, product id, usage and serial number. It is then opened with the original [OpenOptions]
+    /// and the [InitReport]s passed to [DeviceInfo::open_with_init] are sent again, so the device resumes in the same state.
+    /// Returns an [ErrorSource::NotFound] error if the device is not connected.
+    pub async fn reconnect(&mut self) -> HidResult<()> {
+        *self = self
+            .info
+            .rediscover()
+            .await?
+            .open_device(&self.options, self.init_reports.clone())
+            .await?;
+        Ok(())
+    }
+
+    /// Closes this device while keeping everything that is needed to open it again
+    ///
+    /// This releases the OS handle, so other processes can access the device until [WeakDevice::upgrade] is called.
+    pub fn downgrade(self) -> WeakDevice {
+        WeakDevice::new(self.info.clone(), self.options.clone(), self.init_reports.clone())
+    }
+
+    /// The [AccessMode] this device was opened with
+    pub fn mode(&self) -> AccessMode {
+        self.options.mode
     }
 
     /// Retrieves the [DeviceInfo] associated with this device
@@ -114,8 +1074,17 @@ impl Device {
     }
 }
 
+/// A report that gets sent to a device as part of [DeviceInfo::open_with_init] or [OpenOptions::init_report]
+///
+/// The first byte of the data must contain the report id or `0x0` if the device does not use numbered reports
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InitReport {
+    Output(Vec<u8>),
+    Feature(Vec<u8>)
+}
+
 /// An opaque struct that wraps the OS specific identifier of a device
-#[derive(Hash, Clone, Eq, PartialEq)]
+#[derive(Hash, Clone, Eq, PartialEq, Ord, PartialOrd)]
 #[repr(transparent)]
 pub struct DeviceId(BackendDeviceId);
 
@@ -131,6 +1100,113 @@ impl Debug for DeviceId {
     }
 }
 
+/// The bus a device is connected through
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BusType {
+    Usb,
+    /// Bluetooth classic and Bluetooth Low Energy
+    Bluetooth,
+    I2c,
+    Spi,
+    /// Devices that are created by software, i.e with `VirtualDevice` on Linux
+    Virtual,
+    Unknown
+}
+
+/// The way output reports are transferred to the device
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Transport {
+    /// The interrupt out endpoint, falling back to the control endpoint if the device doesn't have one
+    ///
+    /// This is what the OS uses for regular writes on every platform.
+    #[default]
+    Interrupt,
+    /// A `SET_REPORT` request on the control endpoint
+    ///
+    /// Supported on Linux 5.11+ (`HIDIOCSOUTPUT`) and with the Win32 backend (`HidD_SetOutputReport`).
+    /// macOS doesn't let the caller choose, so this behaves like [Transport::Interrupt] there.
+    Control
+}
+
+/// What [Device::read_input_report_for_id] does with the input reports that have a different report id
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum UnmatchedReports {
+    /// The reports are dropped
+    #[default]
+    Discard,
+    /// The reports are queued per report id and returned by later calls for their report id
+    ///
+    /// Only the newest 64 reports are kept per report id. Other reads (i.e [Device::read_input_report]) don't see the queued reports.
+    Buffer
+}
+
+/// The input reports that [Device::read_input_report_for_id] skipped while waiting for another report id
+#[derive(Default)]
+struct SkippedReports(Mutex<HashMap<u8, VecDeque<Vec<u8>>>>);
+
+impl SkippedReports {
+    /// Returns the next report with the given report id, preferring the skipped reports over reading new ones with `read`
+    ///
+    /// `read` gets a buffer of the size of `buf` and hands it back together with the size of the report that was read into it.
+    async fn read_for_id<F, Fut>(&self, unmatched: UnmatchedReports, report_id: u8, buf: &mut [u8], mut read: F) -> HidResult<usize>
+    where
+        F: FnMut(Vec<u8>) -> Fut,
+        Fut: Future<Output = (Vec<u8>, HidResult<usize>)>
+    {
+        if let Some(report) = self.pop(report_id) {
+            let size = report.len().min(buf.len());
+            buf[..size].copy_from_slice(&report[..size]);
+            return Ok(size);
+        }
+        let mut report = vec![0u8; buf.len()];
+        loop {
+            let (returned, result) = read(report).await;
+            report = returned;
+            let size = result?;
+            match report[..size].first() {
+                Some(&id) if id == report_id => {
+                    buf[..size].copy_from_slice(&report[..size]);
+                    return Ok(size);
+                }
+                Some(&id) if unmatched == UnmatchedReports::Buffer => self.push(id, report[..size].to_vec()),
+                id => log::trace!("Discarding input report with id {:?} while waiting for id {}", id, report_id)
+            }
+        }
+    }
+
+    fn pop(&self, report_id: u8) -> Option<Vec<u8>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).get_mut(&report_id)?.pop_front()
+    }
+
+    fn push(&self, report_id: u8, report: Vec<u8>) {
+        let mut queues = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let queue = queues.entry(report_id).or_default();
+        if queue.len() >= router::QUEUE_CAPACITY {
+            log::trace!("Discarding the oldest buffered input report with id {report_id}");
+            queue.pop_front();
+        }
+        queue.push_back(report);
+    }
+
+    /// Drops all skipped reports and returns how many there were
+    fn clear(&self) -> usize {
+        let mut queues = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let count = queues.values().map(VecDeque::len).sum();
+        queues.clear();
+        count
+    }
+}
+
+/// Statistics about the input reports received by a [Device]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct ReadStats {
+    /// The number of input reports that were discarded because they weren't read in time
+    ///
+    /// Only the macOS and WinRT backends can detect this, because they queue the reports themselves.
+    /// The hidraw and Win32 drivers silently drop reports once their buffers are full, so this stays 0 there.
+    pub dropped_reports: u64
+}
+
 /// An enum that controls how a device will be opened
 ///
 /// This mainly influences the flags passed to the underlying OS api,
@@ -145,6 +1221,107 @@ pub enum AccessMode {
     ReadWrite
 }
 
+/// A set of options that control how a device will be opened
+///
+/// There is no option to detach the kernel driver on Linux: the hidraw node receives a copy of every input report,
+/// even while a kernel driver (i.e `hid-generic` or `usbhid`) handles the device as well, and detaching the driver would remove the hidraw node.
+///
+/// ```no_run
+/// # use async_hid::{AccessMode, OpenOptions};
+/// let options = OpenOptions::new()
+///     .mode(AccessMode::Read)
+///     .strip_report_id(true);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct OpenOptions {
+    mode: AccessMode,
+    strip_report_id: bool,
+    busy_retry: Option<RetryPolicy>,
+    filter_by_usage: bool,
+    output_transport: Transport,
+    strict_report_length: bool,
+    unmatched_reports: UnmatchedReports,
+    init_reports: Vec<InitReport>
+}
+
+impl OpenOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The [AccessMode] of the device (default: [AccessMode::ReadWrite])
+    pub fn mode(mut self, mode: AccessMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Removes the report id from input reports of devices that use numbered reports (default: `false`)
+    ///
+    /// By default the first byte of an input report is the report id if the device uses numbered reports
+    /// and the start of the payload otherwise, on every platform.
+    /// Setting this makes the first byte always the start of the payload.
+    /// Whether a device uses numbered reports is determined by [Device::report_ids], so opening fails on backends that don't support it.
+    pub fn strip_report_id(mut self, strip: bool) -> Self {
+        self.strip_report_id = strip;
+        self
+    }
+
+    /// Retries opening the device while it is opened exclusively by another process (default: no retries)
+    ///
+    /// Once all attempts are used up opening fails with [ErrorSource::Busy].
+    /// Exclusive access is only reported on macOS (`kIOReturnExclusiveAccess`), so this has no effect on other platforms.
+    pub fn retry_busy(mut self, policy: RetryPolicy) -> Self {
+        self.busy_retry = Some(policy);
+        self
+    }
+
+    /// Only reads the input reports that belong to the usage of the opened [DeviceInfo] (default: `false`)
+    ///
+    /// Devices with multiple top-level collections are exposed as one [DeviceInfo] per usage, but on Linux and macOS
+    /// all of them share the same underlying device and receive the input reports of every usage.
+    /// With this set, every opened [DeviceInfo] behaves like a separate device that only yields the reports of its own usage,
+    /// matching the behavior of Windows. The reports are assigned to the usages based on their report ids,
+    /// so devices without numbered reports are not filtered.
+    pub fn filter_by_usage(mut self, filter: bool) -> Self {
+        self.filter_by_usage = filter;
+        self
+    }
+
+    /// Fails reads of input reports whose length differs from the one declared by the report descriptor (default: `false`)
+    ///
+    /// [Device::read_input_report] and [Device::try_read_input_report] return an [ErrorSource::LengthMismatch] error
+    /// instead of the report, which helps to catch firmware bugs and truncated reports during development.
+    /// This requires the report descriptor (see [Device::report_descriptor]), so opening fails on platforms that don't provide it.
+    pub fn strict_report_length(mut self, strict: bool) -> Self {
+        self.strict_report_length = strict;
+        self
+    }
+
+    /// How [Device::read_input_report_for_id] handles input reports with other report ids (default: [UnmatchedReports::Discard])
+    pub fn unmatched_reports(mut self, unmatched: UnmatchedReports) -> Self {
+        self.unmatched_reports = unmatched;
+        self
+    }
+
+    /// Adds an [InitReport] that is sent right after opening the device, before it is returned (default: none)
+    ///
+    /// The reports are sent in the order they were added, followed by the reports passed to [DeviceInfo::open_with_init].
+    /// If sending any of the reports fails the device is closed again and the error is returned.
+    /// The reports are sent again whenever the device gets reopened by [Device::reconnect] or [WeakDevice::upgrade].
+    pub fn init_report(mut self, report: InitReport) -> Self {
+        self.init_reports.push(report);
+        self
+    }
+
+    /// The [Transport] used by [Device::write_output_report] (default: [Transport::Interrupt])
+    ///
+    /// Single writes can still use a different transport with [Device::write_output_report_via].
+    pub fn output_transport(mut self, transport: Transport) -> Self {
+        self.output_transport = transport;
+        self
+    }
+}
+
 impl AccessMode {
     pub fn readable(self) -> bool {
         matches!(self, Self::Read | Self::ReadWrite)
@@ -154,5 +1331,87 @@ impl AccessMode {
     }
 }
 
+/// Moves the payload of the report in the first `size` bytes of the buffer to the front and returns its new size
+fn remove_report_id(buf: &mut [u8], size: usize) -> usize {
+    match size {
+        0 => 0,
+        size => {
+            buf.copy_within(1..size, 0);
+            size - 1
+        }
+    }
+}
+
 assert_impl_all!(Device: Send, Sync);
-assert_impl_all!(DeviceInfo: Send, Sync);
\ No newline at end of file
+assert_impl_all!(DeviceInfo: Send, Sync);
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use futures_lite::future::block_on;
+
+    use super::*;
+    use crate::stream::tests::MockSource;
+    use crate::stream::ReportSource;
+
+    /// Reads the next report with the given id from the mock source with [SkippedReports::read_for_id]
+    fn read_for_id(
+        skipped: &SkippedReports, unmatched: UnmatchedReports, source: &RefCell<Option<Box<dyn ReportSource>>>, report_id: u8
+    ) -> HidResult<Vec<u8>> {
+        let mut buf = [0u8; 64];
+        let read = |buf| async move {
+            let (returned, buf, result) = source.take().unwrap().read(buf).await;
+            source.replace(Some(returned));
+            (buf, result)
+        };
+        let size = block_on(skipped.read_for_id(unmatched, report_id, &mut buf, read))?;
+        Ok(buf[..size].to_vec())
+    }
+
+    fn mock_source(reports: &[&[u8]]) -> RefCell<Option<Box<dyn ReportSource>>> {
+        let reports = reports.iter().map(|report| Ok(report.to_vec())).collect();
+        RefCell::new(Some(Box::new(MockSource(reports))))
+    }
+
+    #[test]
+    fn buffer_reports_of_other_ids() {
+        let skipped = SkippedReports::default();
+        let source = mock_source(&[&[0x02, 0xaa], &[0x01, 0xbb], &[0x02, 0xcc]]);
+        assert_eq!(read_for_id(&skipped, UnmatchedReports::Buffer, &source, 0x01).unwrap(), [0x01, 0xbb]);
+        assert_eq!(read_for_id(&skipped, UnmatchedReports::Buffer, &source, 0x02).unwrap(), [0x02, 0xaa]);
+        assert_eq!(read_for_id(&skipped, UnmatchedReports::Buffer, &source, 0x02).unwrap(), [0x02, 0xcc]);
+        assert_eq!(skipped.clear(), 0);
+    }
+
+    #[test]
+    fn discard_reports_of_other_ids() {
+        let skipped = SkippedReports::default();
+        let source = mock_source(&[&[0x02, 0xaa], &[0x01, 0xbb], &[0x02, 0xcc]]);
+        assert_eq!(read_for_id(&skipped, UnmatchedReports::Discard, &source, 0x01).unwrap(), [0x01, 0xbb]);
+        assert_eq!(read_for_id(&skipped, UnmatchedReports::Discard, &source, 0x02).unwrap(), [0x02, 0xcc]);
+        assert_eq!(skipped.clear(), 0);
+    }
+
+    #[test]
+    fn skipped_reports_keep_the_newest() {
+        let skipped = SkippedReports::default();
+        for value in 0..=router::QUEUE_CAPACITY as u8 {
+            skipped.push(0x02, vec![0x02, value]);
+        }
+        assert_eq!(skipped.pop(0x02).unwrap(), [0x02, 0x01]);
+        assert_eq!(skipped.clear(), router::QUEUE_CAPACITY - 1);
+    }
+
+    #[test]
+    fn strip_report_id() {
+        let mut buf = [0x02, 0xaa, 0xbb, 0xff];
+        assert_eq!(remove_report_id(&mut buf, 3), 2);
+        assert_eq!(buf[..2], [0xaa, 0xbb]);
+
+        let mut buf = [0x02, 0xff];
+        assert_eq!(remove_report_id(&mut buf, 1), 0);
+        assert_eq!(remove_report_id(&mut buf, 0), 0);
+        assert!(!OpenOptions::new().strip_report_id);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,92 @@
+//! Recycling of report buffers for high report rates
+
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+/// A pool of report buffers that are reused instead of being allocated for every report
+///
+/// Cloning the pool creates another handle to the same buffers.
+#[derive(Clone)]
+pub struct ReportPool {
+    buffers: Arc<Mutex<Vec<Vec<u8>>>>,
+    capacity: usize
+}
+
+impl ReportPool {
+    /// Creates an empty pool that keeps at most `capacity` unused buffers, additional buffers are freed when they are returned
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffers: Arc::new(Mutex::new(Vec::with_capacity(capacity))),
+            capacity
+        }
+    }
+
+    /// Takes an unused buffer from the pool or allocates a new one, either way resized to the given length
+    pub(crate) fn take(&self, len: usize) -> Vec<u8> {
+        let buffer = self.buffers.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        let mut buffer = buffer.unwrap_or_default();
+        buffer.resize(len, 0);
+        buffer
+    }
+
+    fn recycle(&self, mut buffer: Vec<u8>) {
+        let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+        if buffers.len() < self.capacity {
+            buffer.clear();
+            buffers.push(buffer);
+        }
+    }
+}
+
+impl Debug for ReportPool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReportPool").field("capacity", &self.capacity).finish_non_exhaustive()
+    }
+}
+
+/// A report whose buffer is returned to its [ReportPool] when it is dropped
+pub struct ReportBuf {
+    data: Vec<u8>,
+    pool: ReportPool
+}
+
+impl ReportBuf {
+    pub(crate) fn new(data: Vec<u8>, pool: ReportPool) -> Self {
+        Self { data, pool }
+    }
+
+    /// Takes the buffer out of the pool, it is freed normally instead of being returned
+    pub fn into_vec(mut self) -> Vec<u8> {
+        std::mem::take(&mut self.data)
+    }
+}
+
+impl Deref for ReportBuf {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.data
+    }
+}
+
+impl AsRef<[u8]> for ReportBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl Debug for ReportBuf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&self.data, f)
+    }
+}
+
+impl Drop for ReportBuf {
+    fn drop(&mut self) {
+        // Buffers that were taken with into_vec are empty and not worth keeping
+        if self.data.capacity() > 0 {
+            self.pool.recycle(std::mem::take(&mut self.data));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Owned reports that keep track of their report id
+
+/// The id of a report, `0x0` is used for devices that don't use numbered reports
+///
+/// Functions that take a report id accept anything that converts into a `ReportId`, so plain `u8` values still work.
+/// The separate type prevents report ids and lengths from being mixed up in other places.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ReportId(pub u8);
+
+impl ReportId {
+    /// The report id used for devices that don't use numbered reports
+    pub const UNNUMBERED: Self = Self(0x0);
+
+    /// Whether this is the id of a numbered report
+    pub fn is_numbered(self) -> bool {
+        self != Self::UNNUMBERED
+    }
+}
+
+impl From<u8> for ReportId {
+    fn from(value: u8) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ReportId> for u8 {
+    fn from(value: ReportId) -> Self {
+        value.0
+    }
+}
+
+/// An owned report together with its report id
+///
+/// The report id is always stored in front of the payload, using `0x0` for devices that don't use numbered reports.
+/// This makes it explicit whether a slice contains the report id: [Report::payload] never does, [Report::as_full_bytes] always does.
+/// The full bytes can be passed to functions like [Device::write_output_report](crate::Device::write_output_report) as they are.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Report {
+    data: Vec<u8>
+}
+
+impl Report {
+    /// Creates a report with the given report id (`0x0` for unnumbered reports) and payload
+    pub fn new(report_id: impl Into<ReportId>, payload: &[u8]) -> Self {
+        let mut data = Vec::with_capacity(payload.len() + 1);
+        data.push(report_id.into().0);
+        data.extend_from_slice(payload);
+        Self { data }
+    }
+
+    /// Creates a report from bytes that start with the report id
+    ///
+    /// An empty buffer is treated as an unnumbered report without payload.
+    pub fn from_full_bytes(mut data: Vec<u8>) -> Self {
+        if data.is_empty() {
+            data.push(0x0);
+        }
+        Self { data }
+    }
+
+    /// The report id or `0x0` if the report is unnumbered
+    pub fn id(&self) -> ReportId {
+        ReportId(self.data[0])
+    }
+
+    /// The data of the report without the report id
+    pub fn payload(&self) -> &[u8] {
+        &self.data[1..]
+    }
+
+    /// The report id followed by the payload
+    pub fn as_full_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the report and returns the report id followed by the payload
+    pub fn into_full_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbered_report() {
+        let report = Report::from_full_bytes(vec![0x02, 0xaa, 0xbb]);
+        assert_eq!(report.id(), ReportId(0x02));
+        assert!(report.id().is_numbered());
+        assert_eq!(report.payload(), [0xaa, 0xbb]);
+        assert_eq!(report, Report::new(0x02, &[0xaa, 0xbb]));
+        assert_eq!(report.into_full_bytes(), [0x02, 0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn unnumbered_report() {
+        let report = Report::new(ReportId::UNNUMBERED, &[0xaa, 0xbb]);
+        assert!(!report.id().is_numbered());
+        assert_eq!(report.payload(), [0xaa, 0xbb]);
+        assert_eq!(report.as_full_bytes(), [0x00, 0xaa, 0xbb]);
+
+        let empty = Report::from_full_bytes(Vec::new());
+        assert_eq!(empty.id(), ReportId::UNNUMBERED);
+        assert!(empty.payload().is_empty());
+    }
+}
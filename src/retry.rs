@@ -0,0 +1,61 @@
+use std::future::{ready, Future};
+use std::time::{Duration, Instant};
+
+use crate::{timer, HidError, HidResult};
+
+/// Controls how often an operation that failed gets retried
+///
+/// The delay doubles after every failed attempt.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first one
+    pub attempts: u32,
+    /// The delay before the first retry
+    pub delay: Duration
+}
+
+impl RetryPolicy {
+    /// Only attempt the operation once
+    pub const NONE: Self = Self {
+        attempts: 1,
+        delay: Duration::ZERO
+    };
+
+    pub fn new(attempts: u32, delay: Duration) -> Self {
+        Self { attempts, delay }
+    }
+
+    /// Runs the operation until it succeeds or all attempts are used up and returns the last result
+    // Only the backends whose enumeration can fail transiently make use of this
+    #[cfg_attr(not(any(all(target_os = "windows", feature = "win32"), target_os = "macos")), allow(dead_code))]
+    pub(crate) async fn run<T>(self, mut operation: impl FnMut() -> HidResult<T>) -> HidResult<T> {
+        self.run_while(|| ready(operation()), |_| true).await
+    }
+
+    /// Runs the asynchronous operation until it succeeds, fails with an error that is not retryable or all attempts are used up
+    pub(crate) async fn run_while<T, F>(self, mut operation: impl FnMut() -> F, retryable: impl Fn(&HidError) -> bool) -> HidResult<T>
+    where
+        F: Future<Output = HidResult<T>>
+    {
+        let mut delay = self.delay;
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if attempt < self.attempts && retryable(&e) => {
+                    log::trace!("Attempt {attempt} of {} failed, retrying in {delay:?}\n\tbecause {e:?}", self.attempts);
+                    timer::sleep_until(Instant::now() + delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result
+            }
+        }
+    }
+}
+
+/// Three attempts with an initial delay of 50ms, which adds at most 150ms per failing device
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(3, Duration::from_millis(50))
+    }
+}
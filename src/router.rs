@@ -0,0 +1,231 @@
+use std::collections::{HashMap, VecDeque};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+
+use futures_core::Stream;
+
+use crate::stream::{PendingReport, ReportSource};
+use crate::{Device, Report, ReportId};
+
+/// The number of reports that are kept per report id before the oldest one is discarded
+pub(crate) const QUEUE_CAPACITY: usize = 64;
+
+impl Device {
+    /// Splits the input reports of this device into separate streams per report id
+    ///
+    /// See [ReportRouter] for details.
+    pub fn route_by_id(self) -> ReportRouter {
+        ReportRouter::from_source(Box::new(self))
+    }
+}
+
+/// Distributes the input reports of a single device to one stream per report id
+///
+/// There is no background task: whichever [ReportIdStream] is polled reads the next report from the device and queues it for the stream of its report id.
+/// Reports with ids that no stream was requested for are discarded, as are the oldest reports of a stream that isn't polled for a while.
+/// Failed reads are logged and skipped, all streams end once the device got disconnected.
+///
+/// Can be obtained by calling [Device::route_by_id].
+#[derive(Clone)]
+pub struct ReportRouter {
+    shared: Arc<Shared>
+}
+
+impl ReportRouter {
+    fn from_source(source: Box<dyn ReportSource>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(RouterState {
+                    pending: Some(source.read_report()),
+                    queues: HashMap::new()
+                }),
+                wakers: Arc::new(RouterWaker::default())
+            })
+        }
+    }
+
+    /// Returns a stream of the payloads (without the report id) of all input reports with the given report id
+    ///
+    /// Use `0x0` for devices that don't use numbered reports. Streams for the same report id share their reports.
+    /// Only reports that arrive after this call are routed to the stream.
+    pub fn stream_for(&self, id: impl Into<ReportId>) -> ReportIdStream {
+        let id = id.into();
+        self.shared.state.lock().unwrap_or_else(|e| e.into_inner()).queues.entry(id).or_default();
+        ReportIdStream {
+            shared: self.shared.clone(),
+            id
+        }
+    }
+}
+
+/// A stream of the input reports with a single report id
+///
+/// Can be obtained by calling [ReportRouter::stream_for].
+pub struct ReportIdStream {
+    shared: Arc<Shared>,
+    id: ReportId
+}
+
+impl ReportIdStream {
+    /// The report id of the reports of this stream
+    pub fn id(&self) -> ReportId {
+        self.id
+    }
+}
+
+impl Stream for ReportIdStream {
+    type Item = Vec<u8>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut state = self.shared.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(report) = state.queues.get_mut(&self.id).and_then(VecDeque::pop_front) {
+                return Poll::Ready(Some(report));
+            }
+            let Some(pending) = state.pending.as_mut() else {
+                return Poll::Ready(None);
+            };
+            // The read wakes every stream, as it is unknown which stream will poll it next
+            self.shared.wakers.register(self.id, cx.waker());
+            let waker = Waker::from(self.shared.wakers.clone());
+            let (source, result) = match pending.as_mut().poll(&mut Context::from_waker(&waker)) {
+                Poll::Ready(read) => read,
+                Poll::Pending => return Poll::Pending
+            };
+            match result {
+                Ok(report) => {
+                    state.pending = Some(source.read_report());
+                    state.route(report, &self.shared.wakers);
+                }
+                Err(e) if e.is_disconnected() => {
+                    log::trace!("Ending routed input reports of {:?}\n\tbecause {e:?}", source.name());
+                    state.pending = None;
+                    self.shared.wakers.wake_by_ref();
+                }
+                Err(e) => {
+                    log::trace!("Skipping input report of {:?}\n\tbecause {e:?}", source.name());
+                    state.pending = Some(source.read_report());
+                }
+            }
+        }
+    }
+}
+
+struct Shared {
+    state: Mutex<RouterState>,
+    wakers: Arc<RouterWaker>
+}
+
+struct RouterState {
+    pending: Option<PendingReport>,
+    queues: HashMap<ReportId, VecDeque<Vec<u8>>>
+}
+
+impl RouterState {
+    fn route(&mut self, report: Report, wakers: &RouterWaker) {
+        let Some(queue) = self.queues.get_mut(&report.id()) else {
+            log::trace!("Discarding input report with id {:?} that no stream was requested for", report.id());
+            return;
+        };
+        if queue.len() >= QUEUE_CAPACITY {
+            log::trace!("Discarding the oldest input report with id {:?} because its stream isn't polled", report.id());
+            queue.pop_front();
+        }
+        queue.push_back(report.payload().to_vec());
+        wakers.wake_id(report.id());
+    }
+}
+
+/// Wakes the streams that are waiting for reports
+#[derive(Default)]
+struct RouterWaker(Mutex<HashMap<ReportId, Waker>>);
+
+impl RouterWaker {
+    fn register(&self, id: ReportId, waker: &Waker) {
+        let mut wakers = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        match wakers.get_mut(&id) {
+            Some(registered) => registered.clone_from(waker),
+            None => {
+                wakers.insert(id, waker.clone());
+            }
+        }
+    }
+
+    fn wake_id(&self, id: ReportId) {
+        if let Some(waker) = self.0.lock().unwrap_or_else(|e| e.into_inner()).get(&id) {
+            waker.wake_by_ref();
+        }
+    }
+}
+
+impl Wake for RouterWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).values().for_each(Waker::wake_by_ref);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future::{block_on, poll_once};
+    use futures_lite::StreamExt;
+
+    use super::*;
+    use crate::stream::tests::MockSource;
+    use crate::HidResult;
+
+    fn router(reports: Vec<HidResult<Vec<u8>>>) -> ReportRouter {
+        ReportRouter::from_source(Box::new(MockSource(reports.into())))
+    }
+
+    #[test]
+    fn reports_are_queued_per_id() {
+        let router = router(vec![Ok(vec![0x01, 0xaa]), Ok(vec![0x02, 0xbb]), Ok(vec![0x01, 0xcc])]);
+        let (mut first, mut second) = (router.stream_for(0x01), router.stream_for(0x02));
+        block_on(async {
+            assert_eq!(second.next().await.unwrap(), [0xbb]);
+            assert_eq!(first.next().await.unwrap(), [0xaa]);
+            assert_eq!(first.next().await.unwrap(), [0xcc]);
+            assert_eq!(poll_once(second.next()).await, None);
+        });
+    }
+
+    #[test]
+    fn oldest_reports_are_evicted() {
+        let mut reports: Vec<_> = (0..=QUEUE_CAPACITY as u8).map(|value| Ok(vec![0x02, value])).collect();
+        reports.push(Ok(vec![0x01, 0xaa]));
+        let router = router(reports);
+        let (mut first, mut second) = (router.stream_for(0x01), router.stream_for(0x02));
+        block_on(async {
+            assert_eq!(first.next().await.unwrap(), [0xaa]);
+            for value in 1..=QUEUE_CAPACITY as u8 {
+                assert_eq!(second.next().await.unwrap(), [value]);
+            }
+        });
+    }
+
+    #[test]
+    fn unrequested_ids_are_dropped() {
+        let router = router(vec![Ok(vec![0x03, 0xaa]), Ok(vec![0x01, 0xbb])]);
+        let mut first = router.stream_for(0x01);
+        assert_eq!(block_on(first.next()).unwrap(), [0xbb]);
+        assert!(!router.shared.state.lock().unwrap().queues.contains_key(&ReportId(0x03)));
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn disconnect_ends_all_streams() {
+        let disconnected = std::io::Error::from_raw_os_error(nix::errno::Errno::ENODEV as i32);
+        let router = router(vec![Ok(vec![0x01, 0xaa]), Err(disconnected.into())]);
+        let (mut first, mut second) = (router.stream_for(0x01), router.stream_for(0x02));
+        block_on(async {
+            assert_eq!(second.next().await, None);
+            assert_eq!(first.next().await.unwrap(), [0xaa]);
+            assert_eq!(first.next().await, None);
+        });
+    }
+}
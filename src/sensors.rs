@@ -0,0 +1,28 @@
+//! Helpers for sensors that follow the HID Sensor Usages specification (usage page `0x20`)
+
+use crate::{Device, HidResult};
+
+/// Property: Report Interval
+const REPORT_INTERVAL: u32 = 0x0020_030e;
+/// Property: Change Sensitivity Absolute
+const CHANGE_SENSITIVITY_ABSOLUTE: u32 = 0x0020_030f;
+
+impl Device {
+    /// Sets the interval in which a HID sensor sends input reports
+    ///
+    /// The interval is written as is, so it is interpreted in the unit declared by the sensor (usually milliseconds).
+    /// Returns an [ErrorSource::Unsupported](crate::ErrorSource::Unsupported) error if the sensor has no report interval property
+    /// or the report descriptor is not available on this platform (see [Device::report_descriptor]).
+    pub async fn set_reporting_interval(&self, ms: u32) -> HidResult<()> {
+        self.set_feature_value(REPORT_INTERVAL, ms as i32).await
+    }
+
+    /// Sets the absolute change that is required before a HID sensor sends a new input report
+    ///
+    /// The sensitivity is written as is, so it is interpreted in the unit and exponent declared by the sensor.
+    /// Returns an [ErrorSource::Unsupported](crate::ErrorSource::Unsupported) error if the sensor has no absolute change sensitivity property
+    /// or the report descriptor is not available on this platform (see [Device::report_descriptor]).
+    pub async fn set_sensitivity(&self, sensitivity: u32) -> HidResult<()> {
+        self.set_feature_value(CHANGE_SENSITIVITY_ABSOLUTE, sensitivity as i32).await
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{Device, HidError};
+
+/// A pre-allocated destination for input reports, i.e the producer side of a ring buffer
+///
+/// ```no_run
+/// # use async_hid::ReportSink;
+/// # struct Producer;
+/// # impl Producer { fn slots(&self) -> usize { 0 } fn push(&mut self, _: u8) {} }
+/// struct LengthPrefixed(Producer);
+///
+/// impl ReportSink for LengthPrefixed {
+///     fn push_report(&mut self, report: &[u8]) -> bool {
+///         if self.0.slots() < report.len() + 1 {
+///             return false;
+///         }
+///         self.0.push(report.len() as u8);
+///         report.iter().for_each(|b| self.0.push(*b));
+///         true
+///     }
+/// }
+/// ```
+pub trait ReportSink {
+    /// Stores a copy of the report and returns `false` if there is not enough space left
+    ///
+    /// This is called from the read loop, so it should neither block nor allocate.
+    fn push_report(&mut self, report: &[u8]) -> bool;
+}
+
+impl Device {
+    /// Continuously reads input reports into the given sink until a read fails and returns the error
+    ///
+    /// Reports that don't fit into the sink are dropped and counted in `overflows`, which can be observed from other threads.
+    /// No memory is allocated per report, `buf` is reused for every read.
+    pub async fn pump_input_reports(&self, sink: &mut impl ReportSink, buf: &mut [u8], overflows: &AtomicU64) -> HidError {
+        loop {
+            match self.read_input_report(buf).await {
+                Ok(size) => {
+                    if !sink.push_report(&buf[..size]) {
+                        overflows.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                Err(e) => return e
+            }
+        }
+    }
+}
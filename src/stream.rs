@@ -0,0 +1,383 @@
+use std::future::{poll_fn, Future};
+use std::ops::Range;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures_core::Stream;
+
+use crate::{timer, Device, DeviceInfo, HidError, HidResult, Report, ReportBuf, ReportPool};
+
+pub(crate) type PendingRead = Pin<Box<dyn Future<Output = (Box<dyn ReportSource>, Vec<u8>, HidResult<usize>)> + Send>>;
+pub(crate) type PendingReport = Pin<Box<dyn Future<Output = (Box<dyn ReportSource>, HidResult<Report>)> + Send>>;
+
+/// Something the input reports of a stream are read from, which is a [Device] outside of the tests
+pub(crate) trait ReportSource: Send + 'static {
+    /// Reads the next report into the buffer and hands both back once done
+    fn read(self: Box<Self>, buf: Vec<u8>) -> PendingRead;
+
+    /// Reads the next report as a [Report] that keeps track of the report id and hands it back together with the source
+    fn read_report(self: Box<Self>) -> PendingReport;
+
+    fn name(&self) -> &str;
+}
+
+impl ReportSource for Device {
+    fn read(self: Box<Self>, mut buf: Vec<u8>) -> PendingRead {
+        Box::pin(async move {
+            let result = self.read_input_report(&mut buf).await;
+            (self as Box<dyn ReportSource>, buf, result)
+        })
+    }
+
+    fn read_report(self: Box<Self>) -> PendingReport {
+        Box::pin(async move {
+            // The inherent method, calling it on the box would resolve to this one
+            let result = Device::read_report(&self).await;
+            (self as Box<dyn ReportSource>, result)
+        })
+    }
+
+    fn name(&self) -> &str {
+        &self.info().name
+    }
+}
+
+/// A stream of the input reports of a device
+///
+/// Failed reads are yielded as errors and reading continues with the next report,
+/// so a single malformed report doesn't end the stream.
+/// The stream only ends after yielding an error for which [HidError::is_disconnected](crate::HidError::is_disconnected) returns `true`.
+///
+/// Can be obtained by calling [Device::into_input_reports].
+pub struct InputReportStream {
+    pending: Option<PendingRead>
+}
+
+impl InputReportStream {
+    pub(crate) fn new(device: Device, buffer_size: usize) -> Self {
+        Self::from_source(Box::new(device), buffer_size)
+    }
+
+    fn from_source(source: Box<dyn ReportSource>, buffer_size: usize) -> Self {
+        Self {
+            pending: Some(source.read(vec![0u8; buffer_size]))
+        }
+    }
+
+    /// Whether the stream ended because the device got disconnected
+    pub fn is_ended(&self) -> bool {
+        self.pending.is_none()
+    }
+
+    /// Reads the reports into buffers taken from the given pool instead of allocating a new buffer for every report
+    pub fn pooled(self, pool: ReportPool) -> PooledInputReportStream {
+        PooledInputReportStream { reports: self, pool }
+    }
+
+    /// Suppresses input reports that are identical to the previous report
+    ///
+    /// This is useful for devices that repeat their current state at a high rate even if nothing changed.
+    pub fn dedup(self) -> DedupInputReportStream {
+        DedupInputReportStream {
+            reports: self,
+            previous: None,
+            ranges: Vec::new()
+        }
+    }
+
+    /// Polls the pending read, `map` turns the filled buffer into the item and returns the buffer for the next read
+    fn poll_report<T>(&mut self, cx: &mut Context<'_>, map: impl FnOnce(Vec<u8>, usize) -> (T, Vec<u8>)) -> Poll<Option<HidResult<T>>> {
+        let Some(pending) = self.pending.as_mut() else {
+            return Poll::Ready(None);
+        };
+        let (source, buf, result) = match pending.as_mut().poll(cx) {
+            Poll::Ready(read) => read,
+            Poll::Pending => return Poll::Pending
+        };
+        let (item, buf) = match result {
+            Ok(size) => {
+                let (item, buf) = map(buf, size);
+                (Ok(item), buf)
+            }
+            Err(e) => (Err(e), buf)
+        };
+        self.pending = match &item {
+            Err(e) if e.is_disconnected() => {
+                log::trace!("Ending input report stream of {:?}\n\tbecause {e:?}", source.name());
+                None
+            }
+            _ => Some(source.read(buf))
+        };
+        Poll::Ready(Some(item))
+    }
+
+    /// Waits at most the given duration for the next input report and returns `None` if none arrived in time
+    ///
+    /// Unlike [Device::read_input_report_timeout], which cancels the read once the timeout expires,
+    /// the read stays registered with the OS when this returns `None`, so the next call resumes waiting for the same report.
+    /// This avoids losing readiness that was already signaled for devices with long gaps between reports.
+    /// Fails with [ErrorSource::NotFound](crate::ErrorSource::NotFound) after the stream ended because the device got disconnected.
+    pub async fn next_timeout(&mut self, timeout: Duration) -> HidResult<Option<Vec<u8>>> {
+        let next = poll_fn(|cx| Pin::new(&mut *self).poll_next(cx));
+        match timer::timeout_at(Instant::now() + timeout, next).await {
+            Some(Some(report)) => report.map(Some),
+            Some(None) => Err(HidError::not_found()),
+            None => Ok(None)
+        }
+    }
+}
+
+impl Stream for InputReportStream {
+    type Item = HidResult<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.poll_report(cx, |buf, size| (buf[..size].to_vec(), buf))
+    }
+}
+
+/// A stream of the input reports of a device that recycles the report buffers through a [ReportPool]
+///
+/// Behaves like [InputReportStream], but the buffer that the report was read into is handed out directly,
+/// so no allocation or copy is needed once the pool is warmed up.
+///
+/// Can be obtained by calling [InputReportStream::pooled].
+pub struct PooledInputReportStream {
+    reports: InputReportStream,
+    pool: ReportPool
+}
+
+impl Stream for PooledInputReportStream {
+    type Item = HidResult<ReportBuf>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let pool = &this.pool;
+        this.reports.poll_report(cx, |mut buf, size| {
+            let next = pool.take(buf.len());
+            buf.truncate(size);
+            (ReportBuf::new(buf, pool.clone()), next)
+        })
+    }
+}
+
+/// A stream of the input reports of a device that skips reports which are identical to the previous report
+///
+/// Errors are always yielded and don't affect the comparison.
+///
+/// Can be obtained by calling [InputReportStream::dedup].
+pub struct DedupInputReportStream {
+    reports: InputReportStream,
+    previous: Option<Vec<u8>>,
+    ranges: Vec<Range<usize>>
+}
+
+impl DedupInputReportStream {
+    /// Only compares the given byte ranges of the reports (including the report id), all other bytes may change without a report being yielded
+    ///
+    /// Ranges that exceed the length of a report are cut off at its end. Reports of different lengths are always considered different.
+    pub fn compare_ranges(mut self, ranges: impl IntoIterator<Item = Range<usize>>) -> Self {
+        self.ranges = ranges.into_iter().collect();
+        self
+    }
+
+    fn is_duplicate(&self, report: &[u8]) -> bool {
+        let Some(previous) = &self.previous else {
+            return false;
+        };
+        if previous.len() != report.len() {
+            return false;
+        }
+        if self.ranges.is_empty() {
+            return previous == report;
+        }
+        self.ranges.iter().all(|range| {
+            let end = range.end.min(report.len());
+            let range = range.start.min(end)..end;
+            previous[range.clone()] == report[range]
+        })
+    }
+}
+
+impl Stream for DedupInputReportStream {
+    type Item = HidResult<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match Pin::new(&mut self.reports).poll_next(cx) {
+                Poll::Ready(Some(Ok(report))) => {
+                    if self.is_duplicate(&report) {
+                        continue;
+                    }
+                    self.previous = Some(report.clone());
+                    return Poll::Ready(Some(Ok(report)));
+                }
+                other => return other
+            }
+        }
+    }
+}
+
+/// Waits for the first of the given streams that yields an input report and returns its index together with the report
+///
+/// Unlike [merge_readers] the streams stay owned by the caller, so the returned future can be used in a `select!` together with other events.
+/// Streams that already ended are skipped and `None` is returned once all streams ended.
+/// If several streams are ready, the one with the lowest index wins; rotate the slice between calls if fairness is required.
+pub fn select_reports(streams: &mut [InputReportStream]) -> SelectReports<'_> {
+    SelectReports { streams }
+}
+
+/// A future that resolves to the next input report of any of several streams
+///
+/// Can be obtained by calling [select_reports].
+pub struct SelectReports<'a> {
+    streams: &'a mut [InputReportStream]
+}
+
+impl Future for SelectReports<'_> {
+    type Output = Option<(usize, HidResult<Vec<u8>>)>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        poll_first(self.streams, 0, cx)
+    }
+}
+
+/// Polls the streams beginning at the index `start` and returns the index and item of the first one that yields a report
+///
+/// This is the fan-in that [select_reports], [merge_readers] and [AggregateReader](crate::AggregateReader) are built on.
+/// Streams that already ended are skipped and `None` is returned once all streams ended.
+fn poll_first(streams: &mut [InputReportStream], start: usize, cx: &mut Context<'_>) -> Poll<Option<(usize, HidResult<Vec<u8>>)>> {
+    let count = streams.len();
+    let mut ended = true;
+    for index in (0..count).map(|i| (start + i) % count) {
+        let reports = &mut streams[index];
+        if reports.is_ended() {
+            continue;
+        }
+        ended = false;
+        if let Poll::Ready(Some(report)) = Pin::new(reports).poll_next(cx) {
+            return Poll::Ready(Some((index, report)));
+        }
+    }
+    match ended {
+        true => Poll::Ready(None),
+        false => Poll::Pending
+    }
+}
+
+/// Reads the input reports of multiple devices concurrently and tags every report with the [DeviceInfo] of its device
+///
+/// Failed reads are yielded as errors and the device keeps being read, unless it got disconnected.
+/// Disconnected devices are removed and the stream ends once all devices were removed.
+pub fn merge_readers(readers: Vec<Device>) -> MergedReports {
+    let (infos, streams) = readers
+        .into_iter()
+        .map(|device| {
+            let buffer_size = device.input_buffer_size();
+            (device.info().clone(), device.into_input_reports(buffer_size))
+        })
+        .unzip();
+    MergedReports { infos, streams, next: 0 }
+}
+
+/// A stream of the input reports of multiple devices
+///
+/// Can be obtained by calling [merge_readers].
+pub struct MergedReports {
+    infos: Vec<DeviceInfo>,
+    streams: Vec<InputReportStream>,
+    next: usize
+}
+
+impl MergedReports {
+    /// The [DeviceInfo]s of all devices that are still being read
+    pub(crate) fn sources(&self) -> impl Iterator<Item = &DeviceInfo> {
+        self.infos.iter()
+    }
+}
+
+impl Stream for MergedReports {
+    type Item = (DeviceInfo, HidResult<Vec<u8>>);
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        // Rotate the starting point so that a busy device can't starve the others
+        let start = self.next;
+        let Some((index, report)) = ready!(poll_first(&mut self.streams, start, cx)) else {
+            return Poll::Ready(None);
+        };
+        self.next = index + 1;
+        let info = match self.streams[index].is_ended() {
+            true => {
+                self.streams.remove(index);
+                self.infos.remove(index)
+            }
+            false => self.infos[index].clone()
+        };
+        Poll::Ready(Some((info, report)))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::VecDeque;
+
+    use futures_lite::future::block_on;
+    use futures_lite::StreamExt;
+
+    use super::*;
+
+    /// Replays the given read results and never completes once they are exhausted
+    pub(crate) struct MockSource(pub(crate) VecDeque<HidResult<Vec<u8>>>);
+
+    impl ReportSource for MockSource {
+        fn read(mut self: Box<Self>, mut buf: Vec<u8>) -> PendingRead {
+            Box::pin(async move {
+                let result = match self.0.pop_front() {
+                    Some(result) => result.map(|report| {
+                        buf[..report.len()].copy_from_slice(&report);
+                        report.len()
+                    }),
+                    None => std::future::pending().await
+                };
+                (self as Box<dyn ReportSource>, buf, result)
+            })
+        }
+
+        fn read_report(mut self: Box<Self>) -> PendingReport {
+            Box::pin(async move {
+                let result = match self.0.pop_front() {
+                    Some(result) => result.map(Report::from_full_bytes),
+                    None => std::future::pending().await
+                };
+                (self as Box<dyn ReportSource>, result)
+            })
+        }
+
+        fn name(&self) -> &str {
+            "mock"
+        }
+    }
+
+    #[test]
+    fn bad_report_mid_stream() {
+        let source = MockSource(VecDeque::from([Ok(vec![0x01, 0xaa]), Err(HidError::short_report(1, 2)), Ok(vec![0x01, 0xbb])]));
+        let mut reports = InputReportStream::from_source(Box::new(source), 64);
+        block_on(async {
+            assert_eq!(reports.next().await.unwrap().unwrap(), [0x01, 0xaa]);
+            assert!(reports.next().await.unwrap().is_err());
+            assert!(!reports.is_ended());
+            assert_eq!(reports.next().await.unwrap().unwrap(), [0x01, 0xbb]);
+        });
+    }
+
+    #[test]
+    fn select_first_ready_stream() {
+        let mut streams = [
+            InputReportStream::from_source(Box::new(MockSource(VecDeque::new())), 64),
+            InputReportStream::from_source(Box::new(MockSource(VecDeque::from([Ok(vec![0x02])]))), 64)
+        ];
+        let (index, report) = block_on(select_reports(&mut streams)).unwrap();
+        assert_eq!(index, 1);
+        assert_eq!(report.unwrap(), [0x02]);
+    }
+}
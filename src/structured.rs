@@ -0,0 +1,167 @@
+//! Decoding of input reports into the values declared by the report descriptor
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{ensure, Device, HidError, HidResult, ReportLayout, ReportType};
+
+/// An input report that was decoded based on the report descriptor of the device
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct StructuredReport {
+    /// The id of the report or 0 if the device doesn't use numbered reports
+    pub report_id: u8,
+    /// The logical values of all variable fields (i.e buttons and axes), keyed by their extended usage
+    pub values: BTreeMap<u32, i32>,
+    /// The extended usages that are currently selected in array fields (i.e the pressed keys of a keyboard)
+    pub active: BTreeSet<u32>,
+    /// The data of the report without the report id, for items that can't be decoded
+    pub raw: Vec<u8>
+}
+
+impl StructuredReport {
+    /// The logical value of the given extended usage
+    pub fn value(&self, usage: u32) -> Option<i32> {
+        self.values.get(&usage).copied()
+    }
+
+    /// Whether the given extended usage is set, either as non-zero variable value or as selected array entry
+    pub fn is_active(&self, usage: u32) -> bool {
+        self.active.contains(&usage) || self.value(usage).is_some_and(|value| value != 0)
+    }
+}
+
+impl Device {
+    /// Reads the next input report and decodes it based on the report descriptor
+    ///
+    /// Constant fields (padding) are skipped, the undecoded data is always available as [StructuredReport::raw].
+    /// Returns an [ErrorSource::Unsupported](crate::ErrorSource::Unsupported) error if the report descriptor
+    /// is not available on this platform (see [Device::report_descriptor]).
+    pub async fn read_structured(&self) -> HidResult<StructuredReport> {
+        debug_assert!(self.options.mode.readable());
+        let layout = self.cached_layout().await?;
+        let length = layout
+            .fields
+            .iter()
+            .filter(|field| field.report_type == ReportType::Input)
+            .map(|field| layout.report_length(ReportType::Input, field.report_id))
+            .max()
+            .unwrap_or_default();
+        ensure!(length > 0, HidError::custom("Device declares no input reports"));
+
+        // Bypass the report id stripping, as the id is needed to pick the matching fields
+        let mut buf = vec![0u8; length + 1];
+        let size = loop {
+            let size = self.inner.read_input_report(&mut buf).await?;
+            if self.accepts_report(&buf[..size]) {
+                break size;
+            }
+        };
+        Ok(decode(layout, &buf[..size]))
+    }
+}
+
+/// Decodes an input report as read from the device, including the report id if the device uses numbered reports
+fn decode(layout: &ReportLayout, report: &[u8]) -> StructuredReport {
+    let (report_id, data) = match layout.is_numbered(ReportType::Input) {
+        true => match report.split_first() {
+            Some((id, data)) => (*id, data),
+            None => (0, &report[..0])
+        },
+        false => (0, report)
+    };
+
+    let mut structured = StructuredReport {
+        report_id,
+        raw: data.to_vec(),
+        ..Default::default()
+    };
+    let fields = layout
+        .fields
+        .iter()
+        .filter(|field| field.report_type == ReportType::Input && field.report_id == report_id && !field.is_constant());
+    for field in fields {
+        for index in 0..field.count {
+            let Some(value) = field.extract(data, index) else {
+                break;
+            };
+            match field.is_variable() {
+                true => {
+                    if let Some(usage) = field.usage(index) {
+                        structured.values.insert(usage, value);
+                    }
+                }
+                false => {
+                    // Array fields contain indices into the usage list, usage id 0 means that nothing is selected
+                    let usage = (field.logical_minimum..=field.logical_maximum)
+                        .contains(&value)
+                        .then(|| field.usages.get((value - field.logical_minimum) as usize))
+                        .flatten();
+                    if let Some(usage) = usage.filter(|usage| *usage & 0xffff != 0) {
+                        structured.active.insert(*usage);
+                    }
+                }
+            }
+        }
+    }
+    structured
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::descriptor::tests::{KEYBOARD, NUMBERED};
+
+    /// Three mouse buttons padded to a full byte, the padding declares a usage to make decoding it visible
+    const PADDED: &[u8] = &[
+        0x05, 0x01, // Usage Page (Generic Desktop)
+        0x09, 0x02, // Usage (Mouse)
+        0xa1, 0x01, // Collection (Application)
+        0x05, 0x09, //   Usage Page (Button)
+        0x19, 0x01, //   Usage Minimum (Button 1)
+        0x29, 0x03, //   Usage Maximum (Button 3)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x25, 0x01, //   Logical Maximum (1)
+        0x75, 0x01, //   Report Size (1)
+        0x95, 0x03, //   Report Count (3)
+        0x81, 0x02, //   Input (Data, Variable, Absolute)
+        0x09, 0x04, //   Usage (Button 4)
+        0x75, 0x05, //   Report Size (5)
+        0x95, 0x01, //   Report Count (1)
+        0x81, 0x03, //   Input (Constant, Variable, Absolute)
+        0xc0 // End Collection
+    ];
+
+    #[test]
+    fn keyboard_report() {
+        let layout = ReportLayout::parse(KEYBOARD);
+        // Left Shift followed by the keys A and B
+        let report = decode(&layout, &[0x02, 0x04, 0x05, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(report.report_id, 0);
+        assert_eq!(report.values.len(), 8);
+        assert_eq!(report.value(0x0007_00e0), Some(0));
+        assert_eq!(report.value(0x0007_00e1), Some(1));
+        assert_eq!(report.active, BTreeSet::from([0x0007_0004, 0x0007_0005]));
+        assert_eq!(report.raw, [0x02, 0x04, 0x05, 0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn constant_padding_is_skipped() {
+        let layout = ReportLayout::parse(PADDED);
+        let report = decode(&layout, &[0xff]);
+        assert_eq!(report.values, BTreeMap::from([(0x0009_0001, 1), (0x0009_0002, 1), (0x0009_0003, 1)]));
+        assert_eq!(report.value(0x0009_0004), None);
+    }
+
+    #[test]
+    fn fields_of_the_report_id() {
+        let layout = ReportLayout::parse(NUMBERED);
+        let report = decode(&layout, &[0x03, 0x01]);
+        assert_eq!(report.report_id, 3);
+        assert_eq!(report.values, BTreeMap::from([(0x000c_00ea, 1)]));
+        assert_eq!(report.raw, [0x01]);
+
+        // Report 2 got its size and count back from Pop, the feature report with the same id isn't decoded
+        let report = decode(&layout, &[0x02, 0x01, 0x01, 0x01]);
+        assert_eq!(report.report_id, 2);
+        assert_eq!(report.values, BTreeMap::from([(0x000c_00e2, 1)]));
+    }
+}
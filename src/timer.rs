@@ -0,0 +1,54 @@
+//! Runtime agnostic helpers for time based operations
+
+use std::future::{poll_fn, Future};
+use std::pin::pin;
+#[cfg(not(target_os = "linux"))]
+use std::task::{Context, Waker};
+use std::task::Poll;
+use std::time::Instant;
+
+/// Completes once the given deadline has passed
+///
+/// Under Linux this uses the timer of the selected async runtime, everywhere else the timer of `async-io` is used.
+#[cfg(all(target_os = "linux", feature = "tokio"))]
+pub async fn sleep_until(deadline: Instant) {
+    tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await
+}
+
+/// Completes once the given deadline has passed
+///
+/// Under Linux this uses the timer of the selected async runtime, everywhere else the timer of `async-io` is used.
+#[cfg(not(all(target_os = "linux", feature = "tokio")))]
+pub async fn sleep_until(deadline: Instant) {
+    async_io::Timer::at(deadline).await;
+}
+
+/// Drives the given future until it completes or the deadline passes
+///
+/// Returns `None` if the deadline passed first. The future gets dropped in that case.
+pub async fn timeout_at<F: Future>(deadline: Instant, future: F) -> Option<F::Output> {
+    let mut future = pin!(future);
+    let mut sleep = pin!(sleep_until(deadline));
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(output));
+        }
+        match sleep.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending
+        }
+    })
+    .await
+}
+
+/// Polls the given future exactly once and returns its output if it completed immediately
+///
+/// The future gets dropped if it is still pending.
+#[cfg(not(target_os = "linux"))]
+pub fn poll_once<F: Future>(future: F) -> Option<F::Output> {
+    let mut future = pin!(future);
+    match future.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None
+    }
+}
@@ -0,0 +1,67 @@
+//! Deduplication of output reports that are retried after their write got cancelled
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+
+use crate::backend::DROPPED_WRITES_CONTINUE;
+use crate::{Device, HidResult};
+
+const PENDING: u8 = 0;
+const STARTED: u8 = 1;
+const COMPLETED: u8 = 2;
+
+/// Identifies a single logical write, so retrying it doesn't send the report twice
+///
+/// Create one token per report and pass it to every attempt of [Device::write_output_report_once].
+/// Cloning the token creates another handle to the same write.
+#[derive(Debug, Default, Clone)]
+pub struct WriteToken(Arc<AtomicU8>);
+
+impl WriteToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the report of this write was handed to the OS
+    pub fn is_completed(&self) -> bool {
+        self.0.load(Ordering::Acquire) == COMPLETED
+    }
+}
+
+impl Device {
+    /// Writes an output report at most once per [WriteToken]
+    ///
+    /// When a write is wrapped in a timeout, the future may be dropped after the OS already accepted the report,
+    /// so simply retrying would send the report twice, which stateful devices may mishandle.
+    /// This function instead returns immediately if a previous attempt with the same token completed.
+    /// If a previous attempt got dropped, it waits for the abandoned write (see [Device::flush]) and only sends the report again
+    /// if the platform guarantees that the abandoned write never reached the device:
+    ///
+    /// - Linux and macOS: the report is sent while the future is polled, so dropped attempts are always retried
+    /// - Windows: dropped writes keep running in the background and are treated as sent
+    ///
+    /// On Windows a report may therefore get lost if the attempt was dropped before the write was started, but it is never duplicated.
+    pub async fn write_output_report_once(&self, buf: &[u8], token: &WriteToken) -> HidResult<()> {
+        match token.0.load(Ordering::Acquire) {
+            COMPLETED => return Ok(()),
+            STARTED if DROPPED_WRITES_CONTINUE => {
+                log::trace!("Waiting for an abandoned write instead of sending the report again");
+                self.flush().await?;
+                token.0.store(COMPLETED, Ordering::Release);
+                return Ok(());
+            }
+            _ => {}
+        }
+        token.0.store(STARTED, Ordering::Release);
+        match self.write_output_report(buf).await {
+            Ok(()) => {
+                token.0.store(COMPLETED, Ordering::Release);
+                Ok(())
+            }
+            Err(e) => {
+                token.0.store(PENDING, Ordering::Release);
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Names for extended usages (see the HID Usage Tables specification)
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+/// A source of names for usages that are not part of the bundled table, i.e usages of vendor defined pages
+///
+/// Register it with [set_usage_names].
+pub trait UsageNames: Send + Sync {
+    /// The name of the given extended usage (usage page in the upper 16 bits) or `None` if it is unknown
+    fn usage_name(&self, usage: u32) -> Option<String>;
+}
+
+impl UsageNames for HashMap<u32, String> {
+    fn usage_name(&self, usage: u32) -> Option<String> {
+        self.get(&usage).cloned()
+    }
+}
+
+static CUSTOM_NAMES: RwLock<Option<Box<dyn UsageNames>>> = RwLock::new(None);
+
+/// Registers a source of names that [usage_name] consults for usages that are missing from the bundled table
+///
+/// This replaces the previously registered source.
+pub fn set_usage_names(names: impl UsageNames + 'static) {
+    *CUSTOM_NAMES.write().unwrap_or_else(|e| e.into_inner()) = Some(Box::new(names));
+}
+
+/// Removes the source of names registered with [set_usage_names]
+pub(crate) fn clear_usage_names() {
+    *CUSTOM_NAMES.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The name of the given usage page, i.e `Generic Desktop` for `0x01`
+///
+/// Requires the `usage-names` feature, otherwise this always returns `None`.
+pub fn usage_page_name(page: u16) -> Option<&'static str> {
+    bundled_page_name(page)
+}
+
+/// The name of the given extended usage (usage page in the upper 16 bits)
+///
+/// The bundled table only covers the most common usages, everything else is looked up in the source registered with [set_usage_names].
+/// The bundled table requires the `usage-names` feature.
+pub fn usage_name(usage: u32) -> Option<Cow<'static, str>> {
+    if let Some(name) = bundled_name(usage) {
+        return Some(name);
+    }
+    CUSTOM_NAMES
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_ref()
+        .and_then(|names| names.usage_name(usage))
+        .map(Cow::Owned)
+}
+
+#[cfg(not(feature = "usage-names"))]
+fn bundled_page_name(_page: u16) -> Option<&'static str> {
+    None
+}
+
+#[cfg(feature = "usage-names")]
+fn bundled_page_name(page: u16) -> Option<&'static str> {
+    let name = match page {
+        0x01 => "Generic Desktop",
+        0x02 => "Simulation Controls",
+        0x03 => "VR Controls",
+        0x04 => "Sport Controls",
+        0x05 => "Game Controls",
+        0x06 => "Generic Device Controls",
+        0x07 => "Keyboard/Keypad",
+        0x08 => "LED",
+        0x09 => "Button",
+        0x0a => "Ordinal",
+        0x0b => "Telephony Device",
+        0x0c => "Consumer",
+        0x0d => "Digitizers",
+        0x0e => "Haptics",
+        0x0f => "Physical Input Device",
+        0x10 => "Unicode",
+        0x12 => "Eye and Head Trackers",
+        0x14 => "Auxiliary Display",
+        0x20 => "Sensors",
+        0x40 => "Medical Instrument",
+        0x41 => "Braille Display",
+        0x59 => "Lighting And Illumination",
+        0x80 => "Monitor",
+        0x84 => "Power",
+        0x85 => "Battery System",
+        0x8c => "Barcode Scanner",
+        0x8d => "Scales",
+        0x8e => "Magnetic Stripe Reader",
+        0x90 => "Camera Control",
+        0x91 => "Arcade",
+        0xf1d0 => "FIDO Alliance",
+        0xff00..=0xffff => "Vendor Defined",
+        _ => return None
+    };
+    Some(name)
+}
+
+#[cfg(not(feature = "usage-names"))]
+fn bundled_name(_usage: u32) -> Option<Cow<'static, str>> {
+    None
+}
+
+#[cfg(feature = "usage-names")]
+fn bundled_name(usage: u32) -> Option<Cow<'static, str>> {
+    let page = (usage >> 16) as u16;
+    let id = usage as u16;
+    let name = match (page, id) {
+        // Generic Desktop Page (0x01)
+        (0x01, 0x01) => "Pointer",
+        (0x01, 0x02) => "Mouse",
+        (0x01, 0x04) => "Joystick",
+        (0x01, 0x05) => "Game Pad",
+        (0x01, 0x06) => "Keyboard",
+        (0x01, 0x07) => "Keypad",
+        (0x01, 0x08) => "Multi-axis Controller",
+        (0x01, 0x30) => "X",
+        (0x01, 0x31) => "Y",
+        (0x01, 0x32) => "Z",
+        (0x01, 0x33) => "Rx",
+        (0x01, 0x34) => "Ry",
+        (0x01, 0x35) => "Rz",
+        (0x01, 0x36) => "Slider",
+        (0x01, 0x37) => "Dial",
+        (0x01, 0x38) => "Wheel",
+        (0x01, 0x39) => "Hat Switch",
+        (0x01, 0x80) => "System Control",
+        // LED Page (0x08)
+        (0x08, 0x01) => "Num Lock",
+        (0x08, 0x02) => "Caps Lock",
+        (0x08, 0x03) => "Scroll Lock",
+        (0x08, 0x04) => "Compose",
+        (0x08, 0x05) => "Kana",
+        // Button Page (0x09)
+        (0x09, 0x00) => "No Button Pressed",
+        (0x09, id) => return Some(Cow::Owned(format!("Button {id}"))),
+        // Consumer Page (0x0c)
+        (0x0c, 0x01) => "Consumer Control",
+        (0x0c, 0xb5) => "Scan Next Track",
+        (0x0c, 0xb6) => "Scan Previous Track",
+        (0x0c, 0xcd) => "Play/Pause",
+        (0x0c, 0xe2) => "Mute",
+        (0x0c, 0xe9) => "Volume Increment",
+        (0x0c, 0xea) => "Volume Decrement",
+        _ => return None
+    };
+    Some(Cow::Borrowed(name))
+}
@@ -0,0 +1,56 @@
+//! Access to individual values of reports based on the report descriptor
+
+use crate::{ensure, Device, HidError, HidResult, ReportField, ReportType};
+
+impl Device {
+    /// Reads the value with the given extended usage and returns it together with the field that declares it
+    ///
+    /// Feature reports are preferred as they can be requested at any time.
+    /// Otherwise this waits for the next input report with the matching report id, discarding all other input reports in the meantime.
+    /// Returns `None` if the device doesn't declare the usage in any feature or input report.
+    pub(crate) async fn value(&self, usage: u32) -> HidResult<Option<(ReportField, i32)>> {
+        let layout = self.cached_layout().await?;
+        if let Some((field, index)) = layout.find(ReportType::Feature, usage) {
+            let mut report = vec![0u8; layout.report_length(ReportType::Feature, field.report_id) + 1];
+            report[0] = field.report_id;
+            let size = self.get_feature_report(&mut report).await?;
+            ensure!(size > 0, HidError::custom("Feature report is empty"));
+            return Ok(field.extract(&report[1..size], index).map(|value| (field.clone(), value)));
+        }
+        if let Some((field, index)) = layout.find(ReportType::Input, usage) {
+            let mut report = vec![0u8; layout.report_length(ReportType::Input, field.report_id) + 1];
+            let data = match field.report_id {
+                0x0 => {
+                    let size = self.read_input_report(&mut report).await?;
+                    &report[..size]
+                }
+                id => {
+                    let size = self.read_input_report_for_id(id, &mut report).await?;
+                    match self.strip_report_id {
+                        true => &report[..size],
+                        false => &report[1..size]
+                    }
+                }
+            };
+            return Ok(field.extract(data, index).map(|value| (field.clone(), value)));
+        }
+        Ok(None)
+    }
+
+    /// Replaces the value with the given extended usage in its feature report
+    ///
+    /// The feature report is read first to preserve all other values of the report.
+    /// Returns an [ErrorSource::Unsupported](crate::ErrorSource::Unsupported) error if the device doesn't declare the usage in any feature report.
+    pub(crate) async fn set_feature_value(&self, usage: u32, value: i32) -> HidResult<()> {
+        let layout = self.cached_layout().await?;
+        let (field, index) = layout
+            .find(ReportType::Feature, usage)
+            .ok_or_else(|| HidError::unsupported())?;
+        let mut report = vec![0u8; layout.report_length(ReportType::Feature, field.report_id) + 1];
+        report[0] = field.report_id;
+        self.get_feature_report(&mut report).await?;
+        report[0] = field.report_id;
+        ensure!(field.insert(&mut report[1..], index, value), HidError::custom("Feature report is too short"));
+        self.send_feature_report(&report).await
+    }
+}
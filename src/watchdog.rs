@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+use crate::{Device, HidError, HidResult};
+
+/// A wrapper around a [Device] that detects devices which stopped sending input reports
+///
+/// Unlike [Device::read_input_report_timeout] the watchdog tracks the time since the last received report across multiple reads.
+/// Once the device stays silent for longer than the configured span, reads fail with [ErrorSource::Stalled](crate::ErrorSource::Stalled).
+///
+/// Can be obtained by calling [Device::with_watchdog].
+pub struct Watchdog {
+    device: Device,
+    max_silence: Duration,
+    last_report: Instant
+}
+
+impl Watchdog {
+    pub(crate) fn new(device: Device, max_silence: Duration) -> Self {
+        Self {
+            device,
+            max_silence,
+            last_report: Instant::now()
+        }
+    }
+
+    /// Read a input report from the device or fail if the device has been silent for too long
+    pub async fn read_input_report(&mut self, buf: &mut [u8]) -> HidResult<usize> {
+        let deadline = self.last_report + self.max_silence;
+        match self.device.read_input_report_deadline(buf, deadline).await? {
+            Some(size) => {
+                self.last_report = Instant::now();
+                Ok(size)
+            }
+            None => Err(HidError::stalled())
+        }
+    }
+
+    /// The time that has passed since the last input report was received
+    pub fn silence(&self) -> Duration {
+        self.last_report.elapsed()
+    }
+
+    /// Retrieves the wrapped device, i.e for writing output reports
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Removes the watchdog and returns the wrapped device
+    pub fn into_inner(self) -> Device {
+        self.device
+    }
+}
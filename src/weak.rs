@@ -0,0 +1,37 @@
+use crate::{Device, DeviceInfo, HidResult, InitReport, OpenOptions};
+
+/// A closed [Device] that can be opened again on demand
+///
+/// It keeps the [DeviceInfo] for identification and logging without holding an OS handle,
+/// so it doesn't block other processes from opening the device exclusively.
+///
+/// Can be obtained by calling [Device::downgrade].
+#[derive(Debug, Clone)]
+pub struct WeakDevice {
+    info: DeviceInfo,
+    options: OpenOptions,
+    init_reports: Vec<InitReport>
+}
+
+impl WeakDevice {
+    pub(crate) fn new(info: DeviceInfo, options: OpenOptions, init_reports: Vec<InitReport>) -> Self {
+        Self { info, options, init_reports }
+    }
+
+    /// Opens the device again with the original [OpenOptions] and [InitReport]s
+    ///
+    /// The device is looked up the same way as in [Device::reconnect], so this also works after the device was plugged in again.
+    /// Returns an [ErrorSource::NotFound](crate::ErrorSource::NotFound) error if the device is not connected.
+    pub async fn upgrade(&self) -> HidResult<Device> {
+        self.info
+            .rediscover()
+            .await?
+            .open_device(&self.options, self.init_reports.clone())
+            .await
+    }
+
+    /// The [DeviceInfo] of the device at the time it was downgraded
+    pub fn info(&self) -> &DeviceInfo {
+        &self.info
+    }
+}